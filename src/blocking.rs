@@ -0,0 +1,342 @@
+//! A blocking QStash client, for programs that do not run an async runtime.
+//!
+//! Every method blocks the current thread until the request has finished.
+//! Calling them from within an async runtime panics, use the async [`crate::Client`] there.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # fn run(token: &str, url: &str) -> upstash_qstash::Result<()> {
+//! let qstash = upstash_qstash::blocking::Client::new(token)?;
+//! let body = serde_json::json!({ "key": "value" });
+//! let result = qstash.publish_json(url, &body, None)?;
+//! println!("Published {:?}", result.message_ids());
+//! # Ok(())
+//! # }
+//! ```
+use crate::{
+    utils, BatchItem, DeliveryResponse, Destination, DlqList, DlqMessage, Event, EventFilter,
+    EventList, Message, MessageSettings, PlanLimits, PublishResponse, PublishResponses, Queue,
+    Quota, RateLimit, Schedule, ScheduleResponse, SigningKeys, Topic,
+};
+use bytes::Bytes;
+use reqwest::{header, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use tokio::runtime::Runtime;
+
+/// Blocking QStash client, see the [`crate::Client`] for the documentation of each method.
+///
+/// The client runs the async [`crate::Client`] on a runtime of its own, instead of using
+/// `reqwest::blocking`, so that both clients behave the same. Like `reqwest::blocking`,
+/// its methods panic when called from within an async runtime, and so does dropping it there.
+pub struct Client {
+    inner: crate::Client,
+    runtime: Runtime,
+}
+
+/// Prints the configuration of the async client, but never the token or other headers.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Client {
+    /// Create a new blocking QStash client using your token.
+    pub fn new(token: &str) -> utils::Result<Self> {
+        Self::from_async(crate::Client::new(token)?)
+    }
+
+    /// Create a new blocking QStash client that sends requests to a custom base url.
+    pub fn with_base_url(token: &str, base_url: &str) -> utils::Result<Self> {
+        Self::from_async(crate::Client::with_base_url(token, base_url)?)
+    }
+
+    /// Create a new blocking QStash client from the `QSTASH_TOKEN` and `QSTASH_URL` environment variables.
+    pub fn from_env() -> utils::Result<Self> {
+        Self::from_async(crate::Client::from_env()?)
+    }
+
+    /// Wrap an async client, e.g. one configured with a [`crate::ClientBuilder`].
+    pub fn from_async(client: crate::Client) -> utils::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(utils::QStashError::Runtime)?;
+        Ok(Self {
+            inner: client,
+            runtime,
+        })
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+
+    pub fn plan_limits(&self) -> Option<PlanLimits> {
+        self.inner.plan_limits()
+    }
+
+    pub fn last_rate_limit(&self) -> Option<RateLimit> {
+        self.inner.last_rate_limit()
+    }
+
+    /// The underlying async reqwest client, its requests have to be run on an async runtime.
+    pub fn http(&self) -> &reqwest::Client {
+        self.inner.http()
+    }
+
+    pub fn raw_get(&self, path: &str) -> utils::Result<(StatusCode, header::HeaderMap, Bytes)> {
+        self.block_on(self.inner.raw_get(path))
+    }
+
+    pub fn get_quota(&self) -> utils::Result<Quota> {
+        self.block_on(self.inner.get_quota())
+    }
+
+    pub fn get_message(&self, message_id: &str) -> utils::Result<Message> {
+        self.block_on(self.inner.get_message(message_id))
+    }
+
+    pub fn get_messages(&self, message_ids: &[&str]) -> utils::Result<Vec<utils::Result<Message>>> {
+        self.block_on(self.inner.get_messages(message_ids))
+    }
+
+    pub fn get_message_raw(&self, message_id: &str) -> utils::Result<Value> {
+        self.block_on(self.inner.get_message_raw(message_id))
+    }
+
+    pub fn get_message_body<T: DeserializeOwned>(&self, message_id: &str) -> utils::Result<T> {
+        self.block_on(self.inner.get_message_body(message_id))
+    }
+
+    pub fn publish_json<T, U>(
+        &self,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .publish_json(url_or_topic, body, message_settings),
+        )
+    }
+
+    pub fn publish<T, U>(
+        &self,
+        destination: Destination,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(self.inner.publish(destination, body, message_settings))
+    }
+
+    pub fn publish_fanout<T, U>(
+        &self,
+        urls: &[&str],
+        body: &T,
+        message_settings: U,
+        concurrency: Option<usize>,
+    ) -> utils::Result<Vec<utils::Result<PublishResponse>>>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .publish_fanout(urls, body, message_settings, concurrency),
+        )
+    }
+
+    pub fn publish_bytes<U>(
+        &self,
+        url_or_topic: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .publish_bytes(url_or_topic, body, content_type, message_settings),
+        )
+    }
+
+    pub fn publish_form<U>(
+        &self,
+        url_or_topic: &str,
+        form: &[(&str, &str)],
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .publish_form(url_or_topic, form, message_settings),
+        )
+    }
+
+    pub fn estimate_payload_size<T: Serialize>(&self, body: &T) -> utils::Result<usize> {
+        self.inner.estimate_payload_size(body)
+    }
+
+    pub fn publish_batch(&self, items: Vec<BatchItem>) -> utils::Result<Vec<PublishResponse>> {
+        self.block_on(self.inner.publish_batch(items))
+    }
+
+    pub fn check_destination(&self, url: &str) -> utils::Result<bool> {
+        self.block_on(self.inner.check_destination(url))
+    }
+
+    pub fn create_schedule<U>(
+        &self,
+        destination: &str,
+        cron: &str,
+        message_settings: U,
+    ) -> utils::Result<ScheduleResponse>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .create_schedule(destination, cron, message_settings),
+        )
+    }
+
+    pub fn list_schedules(&self) -> utils::Result<Vec<Schedule>> {
+        self.block_on(self.inner.list_schedules())
+    }
+
+    pub fn get_schedule(&self, schedule_id: &str) -> utils::Result<Schedule> {
+        self.block_on(self.inner.get_schedule(schedule_id))
+    }
+
+    pub fn delete_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        self.block_on(self.inner.delete_schedule(schedule_id))
+    }
+
+    pub fn pause_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        self.block_on(self.inner.pause_schedule(schedule_id))
+    }
+
+    pub fn resume_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        self.block_on(self.inner.resume_schedule(schedule_id))
+    }
+
+    pub fn list_topics(&self) -> utils::Result<Vec<Topic>> {
+        self.block_on(self.inner.list_topics())
+    }
+
+    pub fn delete_topic(&self, name: &str) -> utils::Result<()> {
+        self.block_on(self.inner.delete_topic(name))
+    }
+
+    pub fn add_endpoint(&self, topic: &str, name: &str, url: &str) -> utils::Result<()> {
+        self.block_on(self.inner.add_endpoint(topic, name, url))
+    }
+
+    pub fn remove_endpoint(&self, topic: &str, endpoint_name: &str) -> utils::Result<()> {
+        self.block_on(self.inner.remove_endpoint(topic, endpoint_name))
+    }
+
+    pub fn create_queue(&self, name: &str, parallelism: u32) -> utils::Result<()> {
+        self.block_on(self.inner.create_queue(name, parallelism))
+    }
+
+    pub fn list_queues(&self) -> utils::Result<Vec<Queue>> {
+        self.block_on(self.inner.list_queues())
+    }
+
+    pub fn delete_queue(&self, name: &str) -> utils::Result<()> {
+        self.block_on(self.inner.delete_queue(name))
+    }
+
+    pub fn publish_to_queue<T, U>(
+        &self,
+        queue: &str,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        self.block_on(
+            self.inner
+                .publish_to_queue(queue, url_or_topic, body, message_settings),
+        )
+    }
+
+    pub fn cancel_message(&self, message_id: &str) -> utils::Result<()> {
+        self.block_on(self.inner.cancel_message(message_id))
+    }
+
+    pub fn cancel_messages(&self, message_ids: &[&str]) -> utils::Result<()> {
+        self.block_on(self.inner.cancel_messages(message_ids))
+    }
+
+    pub fn cancel_by_schedule(&self, schedule_id: &str) -> utils::Result<u64> {
+        self.block_on(self.inner.cancel_by_schedule(schedule_id))
+    }
+
+    pub fn dlq_list(&self, cursor: Option<&str>) -> utils::Result<DlqList> {
+        self.block_on(self.inner.dlq_list(cursor))
+    }
+
+    pub fn dlq_list_all(&self, limit: usize) -> utils::Result<Vec<DlqMessage>> {
+        self.block_on(self.inner.dlq_list_all(limit))
+    }
+
+    pub fn dlq_get(&self, dlq_message_id: &str) -> utils::Result<DlqMessage> {
+        self.block_on(self.inner.dlq_get(dlq_message_id))
+    }
+
+    pub fn dlq_delete(&self, dlq_message_id: &str) -> utils::Result<()> {
+        self.block_on(self.inner.dlq_delete(dlq_message_id))
+    }
+
+    pub fn list_events(&self, filter: EventFilter) -> utils::Result<EventList> {
+        self.block_on(self.inner.list_events(filter))
+    }
+
+    pub fn get_message_response(&self, message_id: &str) -> utils::Result<DeliveryResponse> {
+        self.block_on(self.inner.get_message_response(message_id))
+    }
+
+    pub fn list_events_all(&self, filter: EventFilter, limit: usize) -> utils::Result<Vec<Event>> {
+        self.block_on(self.inner.list_events_all(filter, limit))
+    }
+
+    pub fn schedule_messages(
+        &self,
+        schedule_id: &str,
+        cursor: Option<&str>,
+    ) -> utils::Result<EventList> {
+        self.block_on(self.inner.schedule_messages(schedule_id, cursor))
+    }
+
+    pub fn get_signing_keys(&self) -> utils::Result<SigningKeys> {
+        self.block_on(self.inner.get_signing_keys())
+    }
+
+    pub fn rotate_signing_keys(&self) -> utils::Result<SigningKeys> {
+        self.block_on(self.inner.rotate_signing_keys())
+    }
+}
@@ -0,0 +1,333 @@
+#[cfg(not(target_arch = "wasm32"))]
+use crate::request::RequestHook;
+use crate::{retry::RetryPolicy, utils, Client, Plan, Region, BASE_URL};
+use reqwest::header;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// User agent identifying requests sent by this library.
+static DEFAULT_USER_AGENT: &str = concat!("upstash-qstash-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Builder to configure a QStash [`Client`].
+///
+/// # Example
+///
+/// ```no_run
+/// # fn run(token: &str) -> upstash_qstash::Result<()> {
+/// use std::time::Duration;
+///
+/// let qstash = upstash_qstash::Client::builder()
+///     .token(token)
+///     .timeout(Duration::from_secs(5))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ClientBuilder {
+    token: Option<String>,
+    base_url: Option<String>,
+    region: Option<Region>,
+    #[cfg(not(target_arch = "wasm32"))]
+    timeout: Option<Duration>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_max_idle_per_host: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pool_idle_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    http_client: Option<reqwest::Client>,
+    retry_policy: RetryPolicy,
+    default_retries: Option<u32>,
+    default_headers: header::HeaderMap,
+    max_body_size: Option<usize>,
+    retries_limit: Option<u32>,
+    plan: Option<Plan>,
+    #[cfg(not(target_arch = "wasm32"))]
+    on_request: Option<RequestHook>,
+}
+
+/// Prints the configuration of the builder, with the token redacted.
+impl fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("base_url", &self.base_url)
+            .field("region", &self.region)
+            .field("user_agent", &self.user_agent)
+            .field("retry_policy", &self.retry_policy)
+            .field("default_retries", &self.default_retries)
+            .field("max_body_size", &self.max_body_size)
+            .field("plan", &self.plan)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the token used to authenticate against the api.
+    /// The token is the api key of your qstash account.
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Send requests to a custom base url instead of the production api server.
+    ///
+    /// Takes precedence over [`ClientBuilder::region`].
+    pub fn base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.to_string());
+        self
+    }
+
+    /// Send requests to the api server of a region, see [`Region`].
+    ///
+    /// QStash has no header to pin messages to a region, the region is chosen by the url
+    /// of the api server instead.
+    pub fn region(mut self, region: Region) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// Set a timeout for every request, from connecting until the response body has finished.
+    ///
+    /// Not available on wasm32, where requests are sent with the fetch api of the runtime.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum number of idle connections kept open to the api server,
+    /// reqwest keeps an unlimited number by default.
+    ///
+    /// Serverless functions that are frozen between invocations do well with a small pool,
+    /// e.g. 1 or 2 connections. Ignored when a client is passed to [`ClientBuilder::http_client`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = Some(max_idle);
+        self
+    }
+
+    /// Set how long idle connections are kept open, defaults to 90 seconds.
+    ///
+    /// A timeout shorter than the idle timeout of the server avoids reusing connections
+    /// that were closed in the meantime. Ignored when a client is passed to [`ClientBuilder::http_client`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the `User-Agent` header sent with every request,
+    /// defaults to `upstash-qstash-rust/<version>`.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_string());
+        self
+    }
+
+    /// Use a pre-configured reqwest client to send requests.
+    ///
+    /// The authorization header, timeout and user agent are applied to each request
+    /// instead, as they can not be added to an already built client.
+    pub fn http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Retry requests that failed with a transient status, or could not connect,
+    /// up to the given number of times. Requests are not retried by default.
+    ///
+    /// Only GET requests and deduplicated publishes are retried,
+    /// unless [`ClientBuilder::retry_all_requests`] is enabled.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.retry_policy.max_retries = max_retries;
+        self
+    }
+
+    /// Set the base delay of the exponential backoff between retries, defaults to 100ms.
+    ///
+    /// Rate limited requests wait until the limit resets instead,
+    /// when the response contains a `Retry-After` or `RateLimit-Reset` header.
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_policy.backoff = backoff;
+        self
+    }
+
+    /// Set the longest time to wait before a retry, defaults to 30 seconds.
+    ///
+    /// Rate limited requests whose limit resets later are not retried,
+    /// and fail with [`QStashError::RateLimited`](crate::QStashError::RateLimited) instead.
+    pub fn retry_max_delay(mut self, max_delay: Duration) -> Self {
+        self.retry_policy.max_delay = max_delay;
+        self
+    }
+
+    /// Set the status codes of responses that are retried, defaults to 429, 502, 503 and 504.
+    pub fn retry_on(mut self, status_codes: &[u16]) -> Self {
+        self.retry_policy.retry_on = status_codes.to_vec();
+        self
+    }
+
+    /// Retry all requests, including publishes that are not deduplicated.
+    ///
+    /// This can lead to messages being published more than once.
+    pub fn retry_all_requests(mut self, retry_all: bool) -> Self {
+        self.retry_policy.retry_all = retry_all;
+        self
+    }
+
+    /// Set the number of retries of published messages that do not set
+    /// [`MessageSettings::retries`](crate::MessageSettings::retries) themselves.
+    pub fn default_retries(mut self, retries: u32) -> Self {
+        self.default_retries = Some(retries);
+        self
+    }
+
+    /// Set custom headers sent with every published message, e.g. forwarded headers
+    /// like `Upstash-Forward-Api-Version`.
+    ///
+    /// Headers set with [`MessageSettings::custom_headers`](crate::MessageSettings::custom_headers)
+    /// replace default headers with the same name.
+    pub fn default_headers(mut self, headers: header::HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
+
+    /// Reject published messages with a body larger than the given number of bytes,
+    /// before sending them. The maximum size allowed by QStash depends on your plan.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Reject published messages with more retries than the given limit, before sending them.
+    ///
+    /// Use the maximum number of retries of your plan to catch messages the api would reject.
+    /// Retries are not limited by default.
+    pub fn retries_limit(mut self, limit: u32) -> Self {
+        self.retries_limit = Some(limit);
+        self
+    }
+
+    /// Set the plan of your account, see [`Client::plan_limits`].
+    ///
+    /// The limits of the plan are used as [`ClientBuilder::max_body_size`] and
+    /// [`ClientBuilder::retries_limit`], unless those are set explicitly.
+    pub fn plan(mut self, plan: Plan) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+
+    /// Call a function after each request to the api, e.g. to record the latency in your metrics.
+    ///
+    /// It is called with the method, the path of the endpoint, the status of the response
+    /// and the time from sending the request until the response headers arrived, including retries.
+    /// The status is `None` when no response was received.
+    ///
+    /// Not available on wasm32, where the elapsed time can not be measured.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn on_request<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&reqwest::Method, &str, Option<reqwest::StatusCode>, Duration)
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.on_request = Some(Arc::new(hook));
+        self
+    }
+
+    /// Build the [`Client`].
+    ///
+    /// Fails if no token was set.
+    pub fn build(self) -> utils::Result<Client> {
+        let token = self.token.ok_or(utils::QStashError::MissingToken)?;
+        let auth = format!("Bearer {}", token);
+
+        let mut value = header::HeaderValue::from_str(auth.as_str())?;
+        value.set_sensitive(true);
+
+        let mut headers = header::HeaderMap::new();
+        headers.append(header::AUTHORIZATION, value);
+        let user_agent = self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        headers.append(
+            header::USER_AGENT,
+            header::HeaderValue::from_str(user_agent)?,
+        );
+
+        let plan_limits = self.plan.map(Plan::limits);
+        let max_body_size = self
+            .max_body_size
+            .or(plan_limits.map(|limits| limits.max_body_size));
+        let retries_limit = self
+            .retries_limit
+            .or(plan_limits.map(|limits| limits.max_retries));
+
+        let base_url = match (self.base_url, self.region) {
+            (Some(base_url), _) => base_url,
+            (None, Some(region)) => region.base_url(),
+            (None, None) => BASE_URL.to_string(),
+        };
+        let api_base_url = utils::parse_base_url(&base_url)?;
+
+        let client = match self.http_client {
+            Some(http) => Client {
+                http,
+                api_base_url,
+                request_headers: headers,
+                #[cfg(not(target_arch = "wasm32"))]
+                request_timeout: self.timeout,
+                retry_policy: self.retry_policy,
+                default_retries: self.default_retries,
+                default_headers: self.default_headers,
+                max_body_size,
+                retries_limit,
+                plan_limits,
+                #[cfg(not(target_arch = "wasm32"))]
+                on_request: self.on_request,
+                last_rate_limit: Arc::new(Mutex::new(None)),
+            },
+            None => {
+                let builder = reqwest::Client::builder().default_headers(headers);
+                #[cfg(not(target_arch = "wasm32"))]
+                let builder = match self.timeout {
+                    Some(timeout) => builder.timeout(timeout),
+                    None => builder,
+                };
+                #[cfg(not(target_arch = "wasm32"))]
+                let builder = match self.pool_max_idle_per_host {
+                    Some(max_idle) => builder.pool_max_idle_per_host(max_idle),
+                    None => builder,
+                };
+                #[cfg(not(target_arch = "wasm32"))]
+                let builder = match self.pool_idle_timeout {
+                    Some(timeout) => builder.pool_idle_timeout(timeout),
+                    None => builder,
+                };
+
+                Client {
+                    http: builder.build()?,
+                    api_base_url,
+                    request_headers: header::HeaderMap::new(),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    request_timeout: self.timeout,
+                    retry_policy: self.retry_policy,
+                    default_retries: self.default_retries,
+                    default_headers: self.default_headers,
+                    max_body_size,
+                    retries_limit,
+                    plan_limits,
+                    #[cfg(not(target_arch = "wasm32"))]
+                    on_request: self.on_request,
+                    last_rate_limit: Arc::new(Mutex::new(None)),
+                }
+            }
+        };
+
+        Ok(client)
+    }
+}
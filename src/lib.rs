@@ -1,25 +1,111 @@
 //! # Upstash QStash
 //! Unofficial Rust client for [Upstash QStash](https://docs.upstash.com/qstash)
 //! QStash is an HTTP based messaging and scheduling solution for the serverless and edge runtimes.
-use reqwest::{header, Url};
-use serde::Serialize;
+//!
+//! The client uses version 2 of the QStash api, features like queues, flow control
+//! and retry delays are only available there.
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+#[cfg(feature = "stream")]
+use futures::Stream;
+use futures::StreamExt;
+use models::CancelResponse;
+use request::RequestBody;
+use reqwest::{header, Method, StatusCode, Url};
+use retry::RetryPolicy;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{json, Value};
+#[cfg(feature = "stream")]
+use std::collections::VecDeque;
+use std::fmt;
 use std::str;
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
 
-pub use message::MessageSettings;
+pub use builder::ClientBuilder;
+pub use message::{
+    BatchItem, Destination, FlowControl, MessageSettings, PublishRequest, ScheduleBuilder,
+};
+pub use models::{
+    DeliveryResponse, DlqList, DlqMessage, Endpoint, Event, EventFilter, EventList, Message,
+    MessageState, PublishResponse, PublishResponses, Queue, Quota, RateLimit, Schedule,
+    ScheduleResponse, SigningKeys, Timestamp, Topic,
+};
+pub use plan::{Plan, PlanLimits};
+#[cfg(not(target_arch = "wasm32"))]
+pub use qstash::QStash;
+pub use receiver::Receiver;
+pub use region::Region;
 pub use utils::{QStashError, Result};
 
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+pub mod blocking;
+mod builder;
 mod message;
+mod models;
+mod plan;
+#[cfg(not(target_arch = "wasm32"))]
+mod qstash;
+mod receiver;
+mod region;
+mod request;
+mod retry;
+#[cfg(feature = "schema")]
+mod schema;
 mod utils;
 
-/// Url of the qstash api server.
+/// Url of version 2 of the qstash api.
 /// will be the base url for requests via this Client library.
-static BASE_URL: &'static str = "https://qstash.upstash.io/v1/";
+static BASE_URL: &str = "https://qstash.upstash.io/v2/";
+
+/// Timeout of [`Client::check_destination`] for clients without a timeout.
+#[cfg(not(target_arch = "wasm32"))]
+const DESTINATION_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// QStash client
+///
+/// The client is `Send + Sync` and cheap to clone, clones share the connection pool
+/// and the last rate limit, so there is no need to wrap it in an `Arc`.
+#[derive(Clone)]
 pub struct Client {
     http: reqwest::Client,
     api_base_url: Url,
+    /// Headers added to each request, used when the reqwest client was provided by the user.
+    request_headers: header::HeaderMap,
+    /// Timeout applied to each request, as a reqwest client provided by the user
+    /// can not be configured with it.
+    #[cfg(not(target_arch = "wasm32"))]
+    request_timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    /// Retries of messages that do not set their own.
+    default_retries: Option<u32>,
+    /// Custom headers of messages, overridden by the custom headers of the message.
+    default_headers: header::HeaderMap,
+    /// Maximum size of message bodies in bytes.
+    max_body_size: Option<usize>,
+    /// Maximum number of retries of messages.
+    retries_limit: Option<u32>,
+    /// Limits of the plan of the account, if configured.
+    plan_limits: Option<PlanLimits>,
+    /// Called after each request, with the method, path, status and elapsed time.
+    #[cfg(not(target_arch = "wasm32"))]
+    on_request: Option<request::RequestHook>,
+    /// Rate limit reported by the last publish request.
+    last_rate_limit: Arc<Mutex<Option<RateLimit>>>,
+}
+
+/// Prints the configuration of the client, but never the token or other headers.
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("api_base_url", &self.api_base_url.as_str())
+            .field("retry_policy", &self.retry_policy)
+            .field("default_retries", &self.default_retries)
+            .field("max_body_size", &self.max_body_size)
+            .field("plan_limits", &self.plan_limits)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Client {
@@ -27,38 +113,111 @@ impl Client {
     /// The token is the api key of your qstash account.
     /// You can get it from the qstash dashboard.
     pub fn new(token: &str) -> utils::Result<Self> {
-        let auth = format!("Bearer {}", token);
+        Self::builder().token(token).build()
+    }
 
-        let mut value = header::HeaderValue::from_str(auth.as_str())?;
-        value.set_sensitive(true);
+    /// Create a new QStash client that sends requests to a custom base url
+    /// instead of the production api server, e.g. a mock server in tests.
+    ///
+    /// All endpoints are resolved relative to the given url, which should include the
+    /// version of the api, e.g. `https://qstash.upstash.io/v2/`.
+    pub fn with_base_url(token: &str, base_url: &str) -> utils::Result<Self> {
+        Self::builder().token(token).base_url(base_url).build()
+    }
 
-        let mut headers = header::HeaderMap::new();
-        headers.append(header::AUTHORIZATION, value);
+    /// Create a new QStash client that reuses a pre-configured reqwest client,
+    /// sharing its connection pool with the rest of the application.
+    ///
+    /// The token is added to each request, see [`ClientBuilder::http_client`].
+    pub fn with_reqwest(token: &str, http: reqwest::Client) -> utils::Result<Self> {
+        Self::builder().token(token).http_client(http).build()
+    }
 
-        let http = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()?;
+    /// Create a new QStash client from the `QSTASH_TOKEN` environment variable.
+    ///
+    /// Requests are sent to the url in `QSTASH_URL` when it is set, e.g. for a staging server,
+    /// and to the production api server otherwise.
+    /// Fails with [`QStashError::MissingToken`] if `QSTASH_TOKEN` is not set.
+    pub fn from_env() -> utils::Result<Self> {
+        let token = std::env::var("QSTASH_TOKEN").map_err(|_| QStashError::MissingToken)?;
+        let builder = Self::builder().token(&token);
+        match std::env::var("QSTASH_URL") {
+            Ok(base_url) => builder.base_url(&base_url).build(),
+            Err(_) => builder.build(),
+        }
+    }
+
+    /// Create a [`ClientBuilder`] to configure the client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
 
-        let api_base_url = Url::parse(BASE_URL)?;
+    /// The underlying reqwest client, e.g. to call endpoints this crate does not model yet.
+    ///
+    /// The authorization header is already configured as a default header of the client,
+    /// unless a pre-configured client was passed to [`ClientBuilder::http_client`].
+    /// In that case it is only added to the requests sent by this crate.
+    pub fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Send an authenticated GET request to the api and return the response as is,
+    /// without checking the status or parsing the body.
+    ///
+    /// The path is resolved relative to the base url, e.g. `messages/msg_123`.
+    /// Meant for debugging and for endpoints this library does not support yet.
+    ///
+    /// Fails with [`QStashError::InvalidPath`] for absolute urls and paths that
+    /// resolve outside of the base url, as the token would be sent along.
+    pub async fn raw_get(
+        &self,
+        path: &str,
+    ) -> utils::Result<(StatusCode, header::HeaderMap, Bytes)> {
+        let endpoint = self.endpoint(path)?;
+        if !endpoint.as_str().starts_with(self.api_base_url.as_str()) {
+            return Err(QStashError::InvalidPath(path.to_string()));
+        }
+        let response = self
+            .send_request(Method::GET, endpoint, None, None, None)
+            .await?;
 
-        Ok(Self { http, api_base_url })
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        Ok((status, headers, body))
+    }
+
+    /// The limits of the plan configured with [`ClientBuilder::plan`], `None` if no plan was set.
+    pub fn plan_limits(&self) -> Option<PlanLimits> {
+        self.plan_limits
+    }
+
+    /// The rate limit reported by the api with the last request that included one,
+    /// `None` if no request reported one yet. QStash reports rate limits on publish requests.
+    ///
+    /// Use it to slow down before running into the limit.
+    pub fn last_rate_limit(&self) -> Option<RateLimit> {
+        self.last_rate_limit
+            .lock()
+            .ok()
+            .and_then(|last_rate_limit| *last_rate_limit)
     }
 
     /// Get your current quota limits.
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// # async fn run(qstash: upstash_qstash::Client) {
     /// match qstash.get_quota().await {
-    ///     Ok(result) => println!("Quota: {:?}", result),
+    ///     Ok(quota) => println!("Used {} of {}", quota.used, quota.max),
     ///     Err(e) => println!("Error: {}", e),
     /// }
+    /// # }
     /// ```
-    pub async fn get_quota(&self) -> utils::Result<Value> {
-        let endpoint = self.api_base_url.join("quota")?;
-        let response = self.http.get(endpoint).send().await?;
-        let body = response.json().await?;
-        Ok(body)
+    pub async fn get_quota(&self) -> utils::Result<Quota> {
+        let endpoint = self.endpoint("quota")?;
+        self.request(Method::GET, endpoint, None, None).await
     }
 
     /// Get the complete message with the given id
@@ -69,19 +228,72 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// # async fn run(qstash: upstash_qstash::Client, message_id: &str) {
     /// match qstash.get_message(message_id).await {
-    ///     Ok(result) => println!("Result: {:?}", result),
+    ///     Ok(message) => println!("State: {:?}", message.state),
     ///     Err(e) => println!("Error: {}", e),
     /// }
+    /// # }
     /// ```
-    pub async fn get_message(&self, message_id: &str) -> utils::Result<Value> {
-        let endpoint = self
-            .api_base_url
-            .join(format!("messages/{}", message_id).as_str())?;
-        let response = self.http.get(endpoint).send().await?;
-        let body = response.json().await?;
-        Ok(body)
+    pub async fn get_message(&self, message_id: &str) -> utils::Result<Message> {
+        let endpoint = self.resource_endpoint(&["messages", message_id])?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Get multiple messages concurrently, with at most 10 requests in flight.
+    ///
+    /// Returns one result per id, in the same order as the ids, so that missing messages
+    /// do not hide the others.
+    pub async fn get_messages(
+        &self,
+        message_ids: &[&str],
+    ) -> utils::Result<Vec<utils::Result<Message>>> {
+        const CONCURRENCY: usize = 10;
+
+        // Collected first, as a closure in the stream would keep the future from being `Send`.
+        let requests: Vec<_> = message_ids
+            .iter()
+            .map(|message_id| self.get_message(message_id))
+            .collect();
+        Ok(futures::stream::iter(requests)
+            .buffered(CONCURRENCY)
+            .collect()
+            .await)
+    }
+
+    /// Get the message with the given id as raw JSON.
+    ///
+    /// Useful for fields that are not yet part of [`Message`].
+    pub async fn get_message_raw(&self, message_id: &str) -> utils::Result<Value> {
+        let endpoint = self.resource_endpoint(&["messages", message_id])?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Get the body of the message with the given id, deserialized from JSON.
+    ///
+    /// Bodies stored base64 encoded are decoded first.
+    /// A missing body is deserialized from `null`, so use an `Option` if it may be empty.
+    pub async fn get_message_body<T: DeserializeOwned>(
+        &self,
+        message_id: &str,
+    ) -> utils::Result<T> {
+        let message = self.get_message(message_id).await?;
+        if message.body_base64.is_some() {
+            return Ok(serde_json::from_slice(&message.decoded_body()?)?);
+        }
+        let body = match message.body {
+            Some(body) => body,
+            None => return Ok(serde_json::from_value(Value::Null)?),
+        };
+
+        match serde_json::from_str(&body) {
+            Ok(body) => Ok(body),
+            Err(e) => match STANDARD.decode(body.trim()) {
+                Ok(decoded) => Ok(serde_json::from_slice(&decoded)?),
+                Err(_) => Err(e.into()),
+            },
+        }
     }
 
     /// Publish a message to a URL or Topic
@@ -93,31 +305,39 @@ impl Client {
     ///
     /// # Example
     ///
-    /// ```
-    /// use qstash::Client;
-    /// use serde_json::json;
-    ///
-    /// #[tokio::main]
-    /// async fn main() -> Result<(), ()> {
-    ///     let qstash = upstash_qstash::Client::new(token).expect("Init failed");
-    ///     let body = serde_json::json!({
-    ///         "key1": "value1",
-    ///         "key2": "value2"
-    ///     });
-    ///     match qstash
-    ///         .publish_json(
-    ///             url,
-    ///             &body,
-    ///         )
-    ///         .await
-    ///     {
-    ///         Ok(result) => println!("Published {:?}", result),
-    ///         Err(e) => println!("Error: {}", e),
-    ///     }
-    ///     Ok(())
+    /// ```no_run
+    /// # async fn run(token: &str, url: &str) {
+    /// let qstash = upstash_qstash::Client::new(token).expect("Init failed");
+    /// let body = serde_json::json!({
+    ///     "key1": "value1",
+    ///     "key2": "value2"
+    /// });
+    /// match qstash.publish_json(url, &body, None).await {
+    ///     Ok(result) => println!("Published {:?}", result.message_ids()),
+    ///     Err(e) => println!("Error: {}", e),
     /// }
+    /// # }
     /// ```
-    pub async fn publish_json<'a, T, U>(
+    pub async fn publish_json<T, U>(
+        &self,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        let body = self
+            .publish_json_raw(url_or_topic, body, message_settings)
+            .await?;
+        Ok(serde_json::from_value(body)?)
+    }
+
+    /// Publish a message to a URL or Topic and return the raw JSON response.
+    ///
+    /// Takes the same arguments as [`Client::publish_json`].
+    pub async fn publish_json_raw<T, U>(
         &self,
         url_or_topic: &str,
         body: &T,
@@ -125,52 +345,733 @@ impl Client {
     ) -> utils::Result<Value>
     where
         T: Serialize,
-        U: Into<Option<MessageSettings<'a>>>,
+        U: Into<Option<MessageSettings>>,
+    {
+        let endpoint = self.destination_endpoint("publish/", url_or_topic)?;
+        self.publish_json_to(endpoint, body, message_settings.into())
+            .await
+    }
+
+    /// Publish a JSON message to a typed [`Destination`].
+    ///
+    /// Unlike [`Client::publish_json`], a topic name is never mistaken for a url or path.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(qstash: upstash_qstash::Client) -> upstash_qstash::Result<()> {
+    /// use upstash_qstash::Destination;
+    ///
+    /// let body = serde_json::json!({ "key": "value" });
+    /// qstash
+    ///     .publish(Destination::Topic("my-topic".to_string()), &body, None)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn publish<T, U>(
+        &self,
+        destination: Destination,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        let request = PublishRequest::new()
+            .destination(destination)
+            .json_body(body);
+        let request = match message_settings.into() {
+            Some(message_settings) => request.settings(message_settings),
+            None => request,
+        };
+        request.send(self).await
+    }
+
+    /// Publish the same JSON message to multiple URLs concurrently, without creating a topic.
+    ///
+    /// Returns one result per url, in the same order as the urls, so that partial failures
+    /// are visible. Fails as a whole only if the message settings are invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `urls` - The urls of the endpoints to publish to.
+    /// * `body` - The JSON message to publish.
+    /// * `concurrency` - The maximum number of publishes in flight, all at once when `None`.
+    pub async fn publish_fanout<T, U>(
+        &self,
+        urls: &[&str],
+        body: &T,
+        message_settings: U,
+        concurrency: Option<usize>,
+    ) -> utils::Result<Vec<utils::Result<PublishResponse>>>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
     {
-        let endpoint = self
-            .api_base_url
-            .join(format!("publish/{}", url_or_topic).as_str())?;
+        let message_settings = self.message_settings(message_settings.into())?;
+        message_settings.validate()?;
 
-        let message_settings = message_settings.into().unwrap_or(MessageSettings::new());
+        // Collected first, as a closure in the stream would keep the future from being `Send`.
+        let publishes: Vec<_> = urls
+            .iter()
+            .map(|url| {
+                let message_settings = message_settings.clone();
+                async move {
+                    let endpoint =
+                        Destination::Url(url.to_string()).publish_endpoint(&self.api_base_url)?;
+                    let body = self
+                        .publish_json_to(endpoint, body, Some(message_settings))
+                        .await?;
+                    Ok(serde_json::from_value(body)?)
+                }
+            })
+            .collect();
 
-        let payload = json!(body);
-        let response = self
-            .http
-            .post(endpoint)
-            .headers(message_settings.as_headers())
-            .json(&payload)
-            .send()
+        let concurrency = concurrency.unwrap_or(urls.len()).max(1);
+        Ok(futures::stream::iter(publishes)
+            .buffered(concurrency)
+            .collect()
+            .await)
+    }
+
+    /// Fill in the client defaults for the settings that were not set on the message.
+    ///
+    /// Fails if the retries of the message are above the configured limit.
+    fn message_settings(
+        &self,
+        message_settings: Option<MessageSettings>,
+    ) -> utils::Result<MessageSettings> {
+        let mut message_settings = message_settings.unwrap_or_default();
+        if message_settings.retries.is_none() {
+            message_settings.retries = self.default_retries;
+        }
+        if !self.default_headers.is_empty() {
+            let mut headers = self.default_headers.clone();
+            if let Some(custom_headers) = message_settings.custom_headers.take() {
+                headers.extend(custom_headers);
+            }
+            message_settings.custom_headers = Some(headers);
+        }
+        if let (Some(retries), Some(limit)) = (message_settings.retries, self.retries_limit) {
+            if retries > limit {
+                return Err(utils::QStashError::InvalidRetries { retries, limit });
+            }
+        }
+        Ok(message_settings)
+    }
+
+    /// Publish a JSON message to the given publish endpoint.
+    async fn publish_json_to<T: Serialize>(
+        &self,
+        endpoint: Url,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> utils::Result<Value> {
+        let body = RequestBody::Json(json!(body));
+        self.publish_to(endpoint, body, None, message_settings)
+            .await
+    }
+
+    /// Publish a message to the given publish endpoint,
+    /// with the content type of the body if it is not JSON.
+    pub(crate) async fn publish_to(
+        &self,
+        endpoint: Url,
+        body: RequestBody,
+        content_type: Option<&str>,
+        message_settings: Option<MessageSettings>,
+    ) -> utils::Result<Value> {
+        let message_settings = self.message_settings(message_settings)?;
+        message_settings.validate()?;
+
+        let timeout = message_settings.request_timeout();
+        #[cfg(feature = "compression")]
+        let compress = message_settings.compress;
+        let mut headers = message_settings.as_headers()?;
+        if let Some(content_type) = content_type {
+            headers.insert(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_str(content_type)?,
+            );
+        }
+        #[cfg(feature = "compression")]
+        let body = body.compress(compress, &mut headers)?;
+        if self.max_body_size.is_some() {
+            self.check_body_size(body.size()?)?;
+        }
+        self.publish_request(endpoint, headers, body, timeout).await
+    }
+
+    /// The size in bytes of the body [`Client::publish_json`] would send for the given value,
+    /// before compression. Serializes the value the same way, without sending it.
+    pub fn estimate_payload_size<T: Serialize>(&self, body: &T) -> utils::Result<usize> {
+        RequestBody::Json(serde_json::to_value(body)?).size()
+    }
+
+    /// Reject message bodies above the configured maximum size, before sending them.
+    fn check_body_size(&self, size: usize) -> utils::Result<()> {
+        match self.max_body_size {
+            Some(limit) if size > limit => Err(utils::QStashError::BodyTooLarge { size, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Publish raw bytes to a URL or Topic.
+    ///
+    /// Use this to send payloads other than JSON, like plain text, form data or protobuf.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_or_topic` - The url of the endpoint or the name of the topic to publish to.
+    /// * `body` - The raw message body.
+    /// * `content_type` - The content type of the body, forwarded to the destination.
+    pub async fn publish_bytes<U>(
+        &self,
+        url_or_topic: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        let endpoint = self.destination_endpoint("publish/", url_or_topic)?;
+        let body = self
+            .publish_to(
+                endpoint,
+                RequestBody::Bytes(body),
+                Some(content_type),
+                message_settings.into(),
+            )
+            .await?;
+        Ok(serde_json::from_value(body)?)
+    }
+
+    /// Publish form fields to a URL or Topic, encoded as `application/x-www-form-urlencoded`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url_or_topic` - The url of the endpoint or the name of the topic to publish to.
+    /// * `form` - The names and values of the form fields, encoded in the given order.
+    pub async fn publish_form<U>(
+        &self,
+        url_or_topic: &str,
+        form: &[(&str, &str)],
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(form)
+            .finish();
+        self.publish_bytes(
+            url_or_topic,
+            body.into_bytes(),
+            "application/x-www-form-urlencoded",
+            message_settings,
+        )
+        .await
+    }
+
+    /// Publish multiple messages with a single request.
+    ///
+    /// The responses are returned in the same order as the items.
+    pub async fn publish_batch(
+        &self,
+        items: Vec<BatchItem>,
+    ) -> utils::Result<Vec<PublishResponse>> {
+        let endpoint = self.endpoint("batch")?;
+
+        let payload = items
+            .into_iter()
+            .map(|mut item| {
+                self.check_body_size(item.body.len())?;
+                item.settings = self.message_settings(Some(item.settings))?;
+                item.into_json()
+            })
+            .collect::<utils::Result<Vec<Value>>>()?;
+        let body = RequestBody::Json(Value::Array(payload));
+        self.request(Method::POST, endpoint, None, Some(body)).await
+    }
+
+    /// Check whether a destination url responds, before publishing to it or scheduling it.
+    ///
+    /// Sends a `HEAD` request directly to the url, not through QStash. Returns `true` for
+    /// any response, even an error status, and `false` if the url could not be reached
+    /// or did not respond within the timeout of the client, or 10 seconds without one.
+    /// The token of the client is never sent to the destination.
+    pub async fn check_destination(&self, url: &str) -> utils::Result<bool> {
+        let url = Url::parse(url)?;
+
+        // The default headers of the client contain the token, unless the client
+        // was provided by the user and the token is only added to api requests.
+        let http = if self.request_headers.contains_key(header::AUTHORIZATION) {
+            self.http.clone()
+        } else {
+            reqwest::Client::new()
+        };
+
+        let request = http.head(url);
+        #[cfg(not(target_arch = "wasm32"))]
+        let request = request.timeout(self.request_timeout.unwrap_or(DESTINATION_CHECK_TIMEOUT));
+        Ok(request.send().await.is_ok())
+    }
+
+    /// Create a schedule that publishes a message to a URL or Topic periodically.
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The url of the endpoint or the name of the topic to publish to.
+    /// * `cron` - The cron expression of the schedule, evaluated in UTC.
+    pub async fn create_schedule<U>(
+        &self,
+        destination: &str,
+        cron: &str,
+        message_settings: U,
+    ) -> utils::Result<ScheduleResponse>
+    where
+        U: Into<Option<MessageSettings>>,
+    {
+        let endpoint = self.destination_endpoint("schedules/", destination)?;
+
+        let message_settings = self.message_settings(message_settings.into())?;
+        message_settings.validate()?;
+
+        utils::validate_cron(cron)?;
+
+        let mut headers = message_settings.as_headers()?;
+        headers.insert("Upstash-Cron", header::HeaderValue::from_str(cron)?);
+
+        self.request(Method::POST, endpoint, Some(headers), None)
+            .await
+    }
+
+    /// List all schedules of your account.
+    pub async fn list_schedules(&self) -> utils::Result<Vec<Schedule>> {
+        let endpoint = self.endpoint("schedules")?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Get the schedule with the given id.
+    pub async fn get_schedule(&self, schedule_id: &str) -> utils::Result<Schedule> {
+        let endpoint = self.resource_endpoint(&["schedules", schedule_id])?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Delete the schedule with the given id.
+    ///
+    /// No more messages will be published by this schedule.
+    pub async fn delete_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["schedules", schedule_id])?;
+        self.request_no_content(Method::DELETE, endpoint, None, None)
+            .await
+    }
+
+    /// Pause the schedule with the given id, no messages are published until it is resumed.
+    ///
+    /// QStash can not pause or resume single messages, messages that failed
+    /// all retries can be found in the dead letter queue instead.
+    pub async fn pause_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["schedules", schedule_id, "pause"])?;
+        self.request_no_content(Method::POST, endpoint, None, None)
+            .await
+    }
+
+    /// Resume a paused schedule, publishing messages again at the next scheduled time.
+    pub async fn resume_schedule(&self, schedule_id: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["schedules", schedule_id, "resume"])?;
+        self.request_no_content(Method::POST, endpoint, None, None)
+            .await
+    }
+
+    /// List all topics of your account.
+    pub async fn list_topics(&self) -> utils::Result<Vec<Topic>> {
+        let endpoint = self.endpoint("topics")?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Delete the topic with the given name.
+    pub async fn delete_topic(&self, name: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["topics", name])?;
+        self.request_no_content(Method::DELETE, endpoint, None, None)
+            .await
+    }
+
+    /// Add an endpoint to a topic.
+    ///
+    /// The topic is created when its first endpoint is added, there is no endpoint
+    /// to create an empty topic. Messages published to a topic are delivered to all of its endpoints.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The name of the topic.
+    /// * `name` - The name of the endpoint.
+    /// * `url` - The url messages will be delivered to.
+    pub async fn add_endpoint(&self, topic: &str, name: &str, url: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["topics", topic, "endpoints"])?;
+        let body = json!({ "endpoints": [{ "name": name, "url": url }] });
+        self.request_no_content(Method::POST, endpoint, None, Some(RequestBody::Json(body)))
+            .await
+    }
+
+    /// Remove the endpoint with the given name from a topic.
+    pub async fn remove_endpoint(&self, topic: &str, endpoint_name: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["topics", topic, "endpoints"])?;
+        let body = json!({ "endpoints": [{ "name": endpoint_name }] });
+        self.request_no_content(
+            Method::DELETE,
+            endpoint,
+            None,
+            Some(RequestBody::Json(body)),
+        )
+        .await
+    }
+
+    /// Create a queue, or update the parallelism of an existing one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the queue.
+    /// * `parallelism` - The number of messages delivered in parallel, 1 for strict ordering.
+    pub async fn create_queue(&self, name: &str, parallelism: u32) -> utils::Result<()> {
+        let endpoint = self.endpoint("queues")?;
+        let body = json!({ "queueName": name, "parallelism": parallelism });
+        self.request_no_content(Method::POST, endpoint, None, Some(RequestBody::Json(body)))
+            .await
+    }
+
+    /// List all queues of your account.
+    pub async fn list_queues(&self) -> utils::Result<Vec<Queue>> {
+        let endpoint = self.endpoint("queues")?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Delete the queue with the given name.
+    pub async fn delete_queue(&self, name: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["queues", name])?;
+        self.request_no_content(Method::DELETE, endpoint, None, None)
+            .await
+    }
+
+    /// Publish a JSON message to a URL or Topic through a queue.
+    ///
+    /// Messages of a queue are delivered in the order they were enqueued.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - The name of the queue.
+    /// * `url_or_topic` - The url of the endpoint or the name of the topic to publish to.
+    /// * `body` - The JSON message to publish.
+    pub async fn publish_to_queue<T, U>(
+        &self,
+        queue: &str,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: U,
+    ) -> utils::Result<PublishResponses>
+    where
+        T: Serialize,
+        U: Into<Option<MessageSettings>>,
+    {
+        let endpoint = request::append_destination(
+            self.resource_endpoint(&["enqueue", queue, ""])?,
+            url_or_topic,
+        )?;
+        let body = self
+            .publish_json_to(endpoint, body, message_settings.into())
             .await?;
-        let body = response.json().await?;
-        Ok(body)
+        Ok(serde_json::from_value(body)?)
     }
 
     /// Cancel a message. QStash will no longer try to deliver this message to any endpoints.
     ///
     /// All scheduled executions of this message will be canceled as well.
-    pub async fn cancel_message(&self, message_id: &str) -> utils::Result<Value> {
-        let endpoint = self
-            .api_base_url
-            .join(format!("messages/{}", message_id).as_str())?;
-        let response = self.http.delete(endpoint).send().await?;
-        let body = response.json().await?;
-        Ok(body)
+    /// Fails with [`QStashError::NotFound`] if the message does not exist or was already delivered,
+    /// which cleanup code can treat as done.
+    pub async fn cancel_message(&self, message_id: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["messages", message_id])?;
+        self.request_no_content(Method::DELETE, endpoint, None, None)
+            .await
+    }
+
+    /// Cancel multiple messages with a single request.
+    ///
+    /// Nothing is sent if there are no ids, as an empty list would not cancel anything.
+    pub async fn cancel_messages(&self, message_ids: &[&str]) -> utils::Result<()> {
+        if message_ids.is_empty() {
+            return Ok(());
+        }
+
+        let endpoint = self.endpoint("messages")?;
+        let body = json!({ "messageIds": message_ids });
+        self.request_no_content(
+            Method::DELETE,
+            endpoint,
+            None,
+            Some(RequestBody::Json(body)),
+        )
+        .await
     }
 
-    /// Returns the last 100 tasks in descending chronological order.
+    /// Cancel all pending messages published by the schedule with the given id.
+    ///
+    /// Returns the number of canceled messages. The schedule itself keeps publishing new
+    /// messages, pause or delete it first to stop it.
     ///
-    /// Use the cursor parameter to paginate.
-    pub async fn get_tasks(&self, message_id: &str, cursor: Option<i64>) -> utils::Result<Value> {
-        let mut endpoint = self
-            .api_base_url
-            .join(format!("messages/{}/tasks", message_id).as_str())?;
+    /// Fails with [`QStashError::MissingScheduleId`] if the id is empty or blank,
+    /// as the request would then cancel all pending messages of the account.
+    pub async fn cancel_by_schedule(&self, schedule_id: &str) -> utils::Result<u64> {
+        if schedule_id.trim().is_empty() {
+            return Err(QStashError::MissingScheduleId);
+        }
+
+        let endpoint = self.endpoint("messages")?;
+        let body = json!({ "scheduleId": schedule_id });
+        let response: CancelResponse = self
+            .request(
+                Method::DELETE,
+                endpoint,
+                None,
+                Some(RequestBody::Json(body)),
+            )
+            .await?;
+        Ok(response.cancelled)
+    }
+
+    /// List the messages in the dead letter queue.
+    ///
+    /// Use the cursor returned in the [`DlqList`] to paginate.
+    pub async fn dlq_list(&self, cursor: Option<&str>) -> utils::Result<DlqList> {
+        let mut endpoint = self.endpoint("dlq")?;
 
         if let Some(cursor) = cursor {
-            endpoint.set_query(Some(format!("cursor={}", cursor).as_str()));
+            endpoint.query_pairs_mut().append_pair("cursor", cursor);
+        }
+
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// List all messages in the dead letter queue, following the pagination cursor.
+    ///
+    /// Stops after `limit` messages, to avoid loading an unbounded number of messages into memory.
+    pub async fn dlq_list_all(&self, limit: usize) -> utils::Result<Vec<DlqMessage>> {
+        let mut messages = Vec::new();
+        let mut cursor = None;
+        while messages.len() < limit {
+            let page = self.dlq_list(cursor.as_deref()).await?;
+            messages.extend(page.messages);
+            cursor = match page.cursor {
+                Some(next) if !next.is_empty() => Some(next),
+                _ => break,
+            };
+        }
+        messages.truncate(limit);
+        Ok(messages)
+    }
+
+    /// Get a message from the dead letter queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `dlq_message_id` - The id of the message in the dead letter queue, not the original message id.
+    pub async fn dlq_get(&self, dlq_message_id: &str) -> utils::Result<DlqMessage> {
+        let endpoint = self.resource_endpoint(&["dlq", dlq_message_id])?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Delete a message from the dead letter queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `dlq_message_id` - The id of the message in the dead letter queue, not the original message id.
+    pub async fn dlq_delete(&self, dlq_message_id: &str) -> utils::Result<()> {
+        let endpoint = self.resource_endpoint(&["dlq", dlq_message_id])?;
+        self.request_no_content(Method::DELETE, endpoint, None, None)
+            .await
+    }
+
+    /// List events of your messages, like deliveries and failures, in descending chronological order.
+    ///
+    /// Use the cursor returned in the [`EventList`] to paginate.
+    pub async fn list_events(&self, filter: EventFilter) -> utils::Result<EventList> {
+        let mut endpoint = self.endpoint("events")?;
+        filter.apply(&mut endpoint);
+
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Get the response of the destination to the latest delivery attempt of a message.
+    ///
+    /// The response is taken from the events of the message, and fails with
+    /// [`QStashError::NotFound`] if no delivery attempt recorded a response yet.
+    pub async fn get_message_response(&self, message_id: &str) -> utils::Result<DeliveryResponse> {
+        let filter = EventFilter {
+            message_id: Some(message_id.to_string()),
+            ..Default::default()
+        };
+        let events = self.list_events(filter).await?;
+
+        // Events are listed from newest to oldest.
+        events
+            .events
+            .iter()
+            .find_map(Event::response)
+            .ok_or_else(|| {
+                QStashError::NotFound(format!("no response recorded for message {}", message_id))
+            })
+    }
+
+    /// List all events matching the filter, following the pagination cursor.
+    ///
+    /// Stops after `limit` events, to avoid loading an unbounded number of events into memory.
+    pub async fn list_events_all(
+        &self,
+        mut filter: EventFilter,
+        limit: usize,
+    ) -> utils::Result<Vec<Event>> {
+        let mut events = Vec::new();
+        while events.len() < limit {
+            let page = self.list_events(filter.clone()).await?;
+            events.extend(page.events);
+            filter.cursor = match page.cursor {
+                Some(next) if !next.is_empty() => Some(next),
+                _ => break,
+            };
+        }
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    /// List the events of the messages published by the schedule with the given id,
+    /// to trace its delivery history.
+    ///
+    /// The api has no endpoint listing the messages of a schedule, the message ids are
+    /// part of the events. Use the cursor returned in the [`EventList`] to paginate.
+    pub async fn schedule_messages(
+        &self,
+        schedule_id: &str,
+        cursor: Option<&str>,
+    ) -> utils::Result<EventList> {
+        let filter = EventFilter {
+            schedule_id: Some(schedule_id.to_string()),
+            cursor: cursor.map(str::to_string),
+            ..Default::default()
+        };
+        self.list_events(filter).await
+    }
+
+    /// Get the current and next signing keys, used to verify messages with a [`Receiver`].
+    pub async fn get_signing_keys(&self) -> utils::Result<SigningKeys> {
+        let endpoint = self.endpoint("keys")?;
+        self.request(Method::GET, endpoint, None, None).await
+    }
+
+    /// Rotate the signing keys and return the new key pair.
+    ///
+    /// The next key becomes the current key and a new next key is generated.
+    /// Use [`Receiver::update_keys`] to start verifying with the new keys.
+    pub async fn rotate_signing_keys(&self) -> utils::Result<SigningKeys> {
+        let endpoint = self.endpoint("keys/rotate")?;
+        self.request(Method::POST, endpoint, None, None).await
+    }
+
+    /// Stream all events matching the filter, following the pagination cursor until there are no more events.
+    ///
+    /// Errors are yielded as items and end the stream.
+    #[cfg(feature = "stream")]
+    pub fn events_stream(
+        &self,
+        filter: EventFilter,
+    ) -> impl Stream<Item = utils::Result<Event>> + '_ {
+        struct State {
+            events: VecDeque<Event>,
+            filter: EventFilter,
+            done: bool,
         }
 
-        let response = self.http.get(endpoint).send().await?;
-        let body = response.json().await?;
-        Ok(body)
+        let state = State {
+            events: VecDeque::new(),
+            filter,
+            done: false,
+        };
+
+        futures::stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(event) = state.events.pop_front() {
+                    return Some((Ok(event), state));
+                }
+                if state.done {
+                    return None;
+                }
+
+                match self.list_events(state.filter.clone()).await {
+                    Ok(page) => {
+                        state.filter.cursor = page.cursor.filter(|cursor| !cursor.is_empty());
+                        state.done = state.filter.cursor.is_none() || page.events.is_empty();
+                        state.events = page.events.into();
+                    }
+                    Err(e) => {
+                        state.done = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Checks that the api used on edge runtimes builds for them, see the README.
+#[cfg(target_arch = "wasm32")]
+#[allow(dead_code)]
+async fn edge_runtime_publish(token: &str) -> utils::Result<PublishResponses> {
+    let client = Client::new(token)?;
+    client
+        .publish_json("https://example.com/api/webhook", &json!({}), None)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn the_default_client_uses_version_2_of_the_api() {
+        let client = Client::new("token").unwrap();
+        assert_eq!(
+            client.api_base_url.as_str(),
+            "https://qstash.upstash.io/v2/"
+        );
+    }
+
+    #[tokio::test]
+    async fn with_reqwest_reuses_the_client_and_adds_the_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v2/quota"))
+            .and(header("Authorization", "Bearer test-token"))
+            .and(header("X-App", "shared"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut headers = header::HeaderMap::new();
+        headers.insert("X-App", header::HeaderValue::from_static("shared"));
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let mut client = Client::with_reqwest("test-token", http).unwrap();
+        // The production api can not be mocked, so only the base url is replaced.
+        client.api_base_url = utils::parse_base_url(&format!("{}/v2", server.uri())).unwrap();
+        client.get_quota().await.unwrap();
     }
 }
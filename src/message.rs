@@ -1,25 +1,59 @@
-use reqwest::header;
+use crate::{
+    request::{self, RequestBody},
+    utils, Client, PublishResponses, ScheduleResponse,
+};
+use reqwest::{header, Method, Url};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::time::Duration;
 
 /// Optional parameters and configuration for messages
-#[derive(Debug)]
-pub struct MessageSettings<'a> {
-    pub delay: Option<&'a str>,
+#[derive(Debug, Clone)]
+pub struct MessageSettings {
+    pub delay: Option<String>,
+    pub not_before: Option<i64>,
     pub retries: Option<u32>,
-    pub cron: Option<&'a str>,
-    pub callback: Option<&'a str>,
-    pub dedup_id: Option<&'a str>,
+    pub retry_delay: Option<String>,
+    pub cron: Option<String>,
+    pub callback: Option<String>,
+    pub failure_callback: Option<String>,
+    pub dedup_id: Option<String>,
+    pub content_based_dedup: bool,
+    pub method: Option<Method>,
+    pub flow_control: Option<FlowControl>,
+    pub destination_timeout: Option<Duration>,
     pub custom_headers: Option<header::HeaderMap>,
+    pub forward_headers: Vec<(String, String)>,
+    pub callback_forward_headers: Vec<(String, String)>,
+    #[cfg(feature = "compression")]
+    pub compress: Option<usize>,
+    /// Not available on wasm32, where reqwest can not time out requests.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub timeout: Option<Duration>,
 }
 
-impl<'a> MessageSettings<'a> {
+impl MessageSettings {
     pub fn new() -> Self {
         Self {
             delay: None,
+            not_before: None,
             retries: None,
+            retry_delay: None,
             cron: None,
             callback: None,
+            failure_callback: None,
             dedup_id: None,
+            content_based_dedup: false,
+            method: None,
+            flow_control: None,
+            destination_timeout: None,
             custom_headers: None,
+            forward_headers: Vec::new(),
+            callback_forward_headers: Vec::new(),
+            #[cfg(feature = "compression")]
+            compress: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            timeout: None,
         }
     }
 
@@ -32,11 +66,44 @@ impl<'a> MessageSettings<'a> {
     /// 30m = half an hour
     /// 2h = 2 hours
     /// 7d = 7 days
-    pub fn delay(mut self, delay: &'a str) -> Self {
-        self.delay = Some(delay);
+    ///
+    /// Replaces a previously set [`MessageSettings::not_before`].
+    pub fn delay(mut self, delay: impl Into<String>) -> Self {
+        self.delay = Some(delay.into());
+        self.not_before = None;
+        self
+    }
+
+    /// Delay a message by the given duration relative to the time the message was published.
+    ///
+    /// The duration is sent in the largest unit it divides into evenly, e.g. 2 hours as `2h`
+    /// and 90 seconds as `90s`. Fractions of a second are rounded up to the next second,
+    /// so a delay shorter than a second is sent as `1s` instead of no delay.
+    ///
+    /// Replaces a previously set [`MessageSettings::not_before`].
+    pub fn delay_duration(mut self, delay: Duration) -> Self {
+        self.delay = Some(format_duration(delay));
+        self.not_before = None;
         self
     }
 
+    /// Deliver the message at the given unix timestamp in seconds, instead of right away.
+    ///
+    /// Replaces a previously set [`MessageSettings::delay`].
+    pub fn not_before(mut self, unix_seconds: i64) -> Self {
+        self.not_before = Some(unix_seconds);
+        self.delay = None;
+        self
+    }
+
+    /// Deliver the message at the given time, instead of right away.
+    ///
+    /// The time is sent as a unix timestamp in seconds, see [`MessageSettings::not_before`].
+    #[cfg(feature = "chrono")]
+    pub fn not_before_at(self, when: chrono::DateTime<chrono::Utc>) -> Self {
+        self.not_before(when.timestamp())
+    }
+
     /// Set the number of retries for the message.
     /// The maximum number of retries depends on your current plan.
     pub fn retries(mut self, retries: u32) -> Self {
@@ -44,21 +111,40 @@ impl<'a> MessageSettings<'a> {
         self
     }
 
+    /// Set the delay between retries, as an expression evaluated by QStash in milliseconds.
+    ///
+    /// The expression can use the `retried` variable, the number of retries so far,
+    /// and functions like `pow`, `sqrt`, `exp`, `min` and `max`.
+    /// For example `"pow(2, retried) * 1000"` doubles the delay after every retry, starting at 1 second.
+    /// QStash uses an exponential backoff by default.
+    pub fn retry_delay(mut self, expression: impl Into<String>) -> Self {
+        self.retry_delay = Some(expression.into());
+        self
+    }
+
     /// Set the cron expression for the message.
     ///
     /// In addition to sending a message once, you can create a schedule, and QStash will publish the message in the given period.
     /// Schedules can be configured using cron expressions. crontab.guru is a great tool for understanding and creating cron expressions.
     /// QStash uses UTC as timezone when evaluating cron expressions.
-    pub fn cron(mut self, cron: &'a str) -> Self {
-        self.cron = Some(cron);
+    pub fn cron(mut self, cron: impl Into<String>) -> Self {
+        self.cron = Some(cron.into());
         self
     }
 
     /// A callback allows you to call a long running function without having to wait for its response.
     /// Instead of waiting for the request to finish, you can add a callback url to your published message
     /// and when the request finishes, QStash will call your callback URL with the response.
-    pub fn callback_url(mut self, callback_url: &'a str) -> Self {
-        self.callback = Some(callback_url);
+    pub fn callback_url(mut self, callback_url: impl Into<String>) -> Self {
+        self.callback = Some(callback_url.into());
+        self
+    }
+
+    /// A failure callback is called when the message could not be delivered after all retries.
+    /// QStash will call your failure callback URL with the last response of the destination,
+    /// so you can handle permanent failures without polling the dead letter queue.
+    pub fn failure_callback_url(mut self, failure_callback_url: impl Into<String>) -> Self {
+        self.failure_callback = Some(failure_callback_url.into());
         self
     }
 
@@ -66,39 +152,785 @@ impl<'a> MessageSettings<'a> {
     /// When a duplicate message is detected, it is accepted by QStash but not enqueued.
     /// This can be useful when the connection between your service and QStash fails, and you never receive the acknowledgement.
     /// You can simply retry publishing and can be sure that the message will enqueued only once.
-    pub fn dedup_id(mut self, dedup_id: &'a str) -> Self {
-        self.dedup_id = Some(dedup_id);
+    ///
+    /// QStash remembers deduplication ids for a fixed window of time that can not be set
+    /// per message. For "only once within the last hour" semantics, include the hour
+    /// in the id instead, e.g. `order-42-2024-05-01T10`.
+    pub fn dedup_id(mut self, dedup_id: impl Into<String>) -> Self {
+        self.dedup_id = Some(dedup_id.into());
+        self
+    }
+
+    /// Deduplicate messages based on their content instead of an explicit [`MessageSettings::dedup_id`].
+    ///
+    /// QStash computes the deduplication id from the destination, the body and the forwarded headers,
+    /// so publishing identical messages more than once only enqueues them once.
+    /// An explicit deduplication id is not needed when this is enabled.
+    pub fn content_based_dedup(mut self, enabled: bool) -> Self {
+        self.content_based_dedup = enabled;
+        self
+    }
+
+    /// Set the HTTP method QStash uses to deliver the message to the destination.
+    /// Messages are delivered with POST by default.
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Limit the deliveries of all messages sharing the same flow control key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Messages with the same key share the limits.
+    /// * `rate` - The maximum number of deliveries per second.
+    /// * `parallelism` - The maximum number of deliveries in progress at the same time.
+    pub fn flow_control(mut self, key: impl Into<String>, rate: u32, parallelism: u32) -> Self {
+        self.flow_control = Some(FlowControl {
+            key: key.into(),
+            rate,
+            parallelism,
+        });
+        self
+    }
+
+    /// Set how long QStash waits for the destination to respond to a delivery,
+    /// before the delivery fails and is retried.
+    ///
+    /// Unlike `MessageSettings::timeout`, this limits the delivery to the destination,
+    /// not the publish request. Sent in the largest unit it divides into evenly, like
+    /// [`MessageSettings::delay_duration`].
+    pub fn destination_timeout(mut self, timeout: Duration) -> Self {
+        self.destination_timeout = Some(timeout);
         self
     }
 
     /// In addition to sending the message itself, you can also forward HTTP headers.
+    ///
+    /// The headers are sent to QStash as-is, so only headers prefixed with `Upstash-Forward-`
+    /// reach the destination. Use [`MessageSettings::forward_header`] to add the prefix automatically.
     pub fn custom_headers(mut self, custom_headers: header::HeaderMap) -> Self {
         self.custom_headers = Some(custom_headers);
         self
     }
 
-    pub fn as_headers(self) -> header::HeaderMap {
+    /// Add custom headers from name and value pairs, see [`MessageSettings::custom_headers`].
+    ///
+    /// Returns an error instead of panicking if a name or value is not a valid header.
+    pub fn headers_from<'a, I>(mut self, pairs: I) -> utils::Result<Self>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let headers = self
+            .custom_headers
+            .get_or_insert_with(header::HeaderMap::new);
+        for (name, value) in pairs {
+            headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes())?,
+                header::HeaderValue::from_str(value)?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// Forward an HTTP header to the destination.
+    ///
+    /// The name is prefixed with `Upstash-Forward-`, so `X-My-Header` is sent to QStash
+    /// as `Upstash-Forward-X-My-Header` and delivered to the destination as `X-My-Header`.
+    pub fn forward_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.forward_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Forward an HTTP header to the callback url.
+    ///
+    /// The name is prefixed with `Upstash-Callback-Forward-`, so `X-My-Header` is sent to QStash
+    /// as `Upstash-Callback-Forward-X-My-Header` and delivered to the callback as `X-My-Header`.
+    pub fn callback_forward_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.callback_forward_headers
+            .push((name.into(), value.into()));
+        self
+    }
+
+    /// Validate the settings before they are sent to the api.
+    ///
+    /// Cron expressions are only checked with the `cron-validation` feature enabled.
+    pub(crate) fn validate(&self) -> utils::Result<()> {
+        if let Some(cron) = &self.cron {
+            utils::validate_cron(cron)?;
+        }
+        Ok(())
+    }
+
+    /// Gzip the body of the message when it is at least `min_size` bytes long.
+    ///
+    /// The destination receives the compressed body with a `Content-Encoding: gzip` header,
+    /// so it has to decompress the body itself. Small bodies are sent as is,
+    /// as compressing them costs more than it saves.
+    #[cfg(feature = "compression")]
+    pub fn compress(mut self, min_size: usize) -> Self {
+        self.compress = Some(min_size);
+        self
+    }
+
+    /// Set a timeout for the publish request of this message,
+    /// replacing the timeout configured on the client.
+    ///
+    /// This limits how long publishing to QStash may take, not the delivery to the destination.
+    /// Not available on wasm32, where requests are sent with the fetch api of the runtime,
+    /// which reqwest can not time out.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The timeout of the publish request, if one was set.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn request_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn request_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Convert the settings into the headers sent to QStash.
+    ///
+    /// Fails if a value can not be sent as a header, e.g. a callback url with invalid characters.
+    pub fn as_headers(self) -> utils::Result<header::HeaderMap> {
         let mut headers = header::HeaderMap::new();
 
         if let Some(delay) = self.delay {
-            headers.insert("Upstash-Delay", delay.to_string().parse().unwrap());
+            headers.insert("Upstash-Delay", delay.parse()?);
+        }
+        if let Some(not_before) = self.not_before {
+            headers.insert("Upstash-Not-Before", not_before.to_string().parse()?);
         }
         if let Some(retries) = self.retries {
-            headers.insert("Upstash-Retries", retries.to_string().parse().unwrap());
+            headers.insert("Upstash-Retries", retries.to_string().parse()?);
+        }
+        if let Some(retry_delay) = self.retry_delay {
+            headers.insert("Upstash-Retry-Delay", retry_delay.parse()?);
         }
         if let Some(cron) = self.cron {
-            headers.insert("Upstash-Cron", cron.parse().unwrap());
+            headers.insert("Upstash-Cron", cron.parse()?);
         }
         if let Some(callback) = self.callback {
-            headers.insert("Upstash-Callback", callback.parse().unwrap());
+            headers.insert("Upstash-Callback", callback.parse()?);
+        }
+        if let Some(failure_callback) = self.failure_callback {
+            headers.insert("Upstash-Failure-Callback", failure_callback.parse()?);
         }
         if let Some(dedup_id) = self.dedup_id {
-            headers.insert("Upstash-Deduplication-Id", dedup_id.parse().unwrap());
+            headers.insert("Upstash-Deduplication-Id", dedup_id.parse()?);
+        }
+        if self.content_based_dedup {
+            headers.insert(
+                "Upstash-Content-Based-Deduplication",
+                header::HeaderValue::from_static("true"),
+            );
+        }
+        if let Some(method) = self.method {
+            headers.insert("Upstash-Method", method.as_str().parse()?);
+        }
+        if let Some(flow_control) = self.flow_control {
+            headers.insert("Upstash-Flow-Control-Key", flow_control.key.parse()?);
+            headers.insert(
+                "Upstash-Flow-Control-Value",
+                format!(
+                    "rate={}, parallelism={}",
+                    flow_control.rate, flow_control.parallelism
+                )
+                .parse()?,
+            );
+        }
+        if let Some(destination_timeout) = self.destination_timeout {
+            headers.insert(
+                "Upstash-Timeout",
+                format_duration(destination_timeout).parse()?,
+            );
         }
         if let Some(custom_headers) = self.custom_headers {
             headers.extend(custom_headers);
         }
+        for (name, value) in self.forward_headers {
+            let name = format!("Upstash-Forward-{}", name);
+            headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes())?,
+                value.parse()?,
+            );
+        }
+        for (name, value) in self.callback_forward_headers {
+            let name = format!("Upstash-Callback-Forward-{}", name);
+            headers.insert(
+                header::HeaderName::from_bytes(name.as_bytes())?,
+                value.parse()?,
+            );
+        }
+
+        Ok(headers)
+    }
+}
+
+/// Limits for the deliveries of messages sharing a flow control key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlowControl {
+    pub key: String,
+    pub rate: u32,
+    pub parallelism: u32,
+}
+
+impl Default for MessageSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Format a duration as (number)(unit), using the largest unit that represents it exactly.
+///
+/// Fractions of a second are rounded up, as QStash does not accept smaller units.
+fn format_duration(duration: Duration) -> String {
+    const UNITS: [(u64, &str); 3] = [(24 * 60 * 60, "d"), (60 * 60, "h"), (60, "m")];
+
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    for (unit_seconds, unit) in UNITS {
+        // `u64::is_multiple_of` would require Rust 1.87.
+        #[allow(clippy::manual_is_multiple_of)]
+        if seconds > 0 && seconds % unit_seconds == 0 {
+            return format!("{}{}", seconds / unit_seconds, unit);
+        }
+    }
+    format!("{}s", seconds)
+}
+
+/// Where a message is published to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    /// The url of an endpoint, e.g. `https://example.com/api/webhook`.
+    Url(String),
+    /// The name of a topic, delivering the message to all endpoints of the topic.
+    Topic(String),
+}
+
+impl Destination {
+    /// Resolve the publish endpoint for this destination.
+    pub(crate) fn publish_endpoint(&self, api_base_url: &Url) -> utils::Result<Url> {
+        match self {
+            Destination::Url(url) => {
+                request::append_destination(api_base_url.join("publish/")?, url)
+            }
+            Destination::Topic(topic) => {
+                request::check_segments([topic.as_str()].into_iter())?;
+                let mut endpoint = api_base_url.join("publish/")?;
+                // Pushed as a single, percent-encoded path segment, so the name is never treated as a path.
+                endpoint
+                    .path_segments_mut()
+                    .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+                    .pop_if_empty()
+                    .push(topic);
+                Ok(endpoint)
+            }
+        }
+    }
+}
+
+/// A single message of a batch publish.
+#[derive(Debug)]
+pub struct BatchItem {
+    pub destination: String,
+    pub body: String,
+    pub content_type: String,
+    pub settings: MessageSettings,
+}
+
+impl BatchItem {
+    /// Create a batch item publishing the body to a URL or Topic.
+    pub fn new(destination: &str, body: &str, content_type: &str) -> Self {
+        Self {
+            destination: destination.to_string(),
+            body: body.to_string(),
+            content_type: content_type.to_string(),
+            settings: MessageSettings::new(),
+        }
+    }
+
+    /// Set the optional parameters and configuration for this message.
+    pub fn settings(mut self, settings: MessageSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Convert the item into the JSON object expected by the batch endpoint,
+    /// with the message settings nested as headers.
+    pub(crate) fn into_json(self) -> utils::Result<Value> {
+        self.settings.validate()?;
+
+        let mut headers = Map::new();
+        headers.insert(
+            header::CONTENT_TYPE.to_string(),
+            Value::String(self.content_type),
+        );
+        for (name, value) in self.settings.as_headers()?.iter() {
+            headers.insert(
+                name.to_string(),
+                Value::String(String::from_utf8_lossy(value.as_bytes()).into_owned()),
+            );
+        }
+
+        Ok(json!({
+            "destination": self.destination,
+            "headers": headers,
+            "body": self.body,
+        }))
+    }
+}
+
+/// A publish request, built step by step.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run(qstash: upstash_qstash::Client) -> upstash_qstash::Result<()> {
+/// use upstash_qstash::{Destination, MessageSettings, PublishRequest};
+///
+/// PublishRequest::new()
+///     .destination(Destination::Url("https://example.com/api/webhook".to_string()))
+///     .json_body(&serde_json::json!({ "key": "value" }))
+///     .settings(MessageSettings::new().retries(3))
+///     .send(&qstash)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PublishRequest {
+    destination: Option<Destination>,
+    body: Option<PublishBody>,
+    settings: Option<MessageSettings>,
+}
+
+#[derive(Debug, Clone)]
+enum PublishBody {
+    Json(Value),
+    Bytes { body: Vec<u8>, content_type: String },
+}
+
+impl PublishRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set where the message is published to.
+    pub fn destination(mut self, destination: Destination) -> Self {
+        self.destination = Some(destination);
+        self
+    }
+
+    /// Publish the given value as JSON, replacing a previously set body.
+    pub fn json_body<T: Serialize>(mut self, body: &T) -> Self {
+        self.body = Some(PublishBody::Json(json!(body)));
+        self
+    }
+
+    /// Publish raw bytes with the given content type, replacing a previously set body.
+    pub fn bytes_body(mut self, body: Vec<u8>, content_type: &str) -> Self {
+        self.body = Some(PublishBody::Bytes {
+            body,
+            content_type: content_type.to_string(),
+        });
+        self
+    }
+
+    /// Set the optional parameters and configuration for this message.
+    pub fn settings(mut self, settings: MessageSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Publish the message with the given client.
+    ///
+    /// Fails if no destination was set. Messages without a body are published with an empty body.
+    pub async fn send(self, client: &Client) -> utils::Result<PublishResponses> {
+        let destination = self
+            .destination
+            .ok_or(utils::QStashError::MissingDestination)?;
+        let endpoint = destination.publish_endpoint(&client.api_base_url)?;
+
+        let (body, content_type) = match self.body {
+            Some(PublishBody::Json(body)) => (RequestBody::Json(body), None),
+            Some(PublishBody::Bytes { body, content_type }) => {
+                (RequestBody::Bytes(body), Some(content_type))
+            }
+            None => (RequestBody::Bytes(Vec::new()), None),
+        };
+        let body = client
+            .publish_to(endpoint, body, content_type.as_deref(), self.settings)
+            .await?;
+        Ok(serde_json::from_value(body)?)
+    }
+}
+
+/// A schedule, built step by step.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn run(qstash: upstash_qstash::Client) -> upstash_qstash::Result<()> {
+/// use upstash_qstash::ScheduleBuilder;
+///
+/// ScheduleBuilder::new()
+///     .destination("https://example.com/api/cleanup")
+///     .cron("0 3 * * *")
+///     .retries(3)
+///     .callback("https://example.com/api/cleanup-done")
+///     .forward_header("X-Job", "cleanup")
+///     .create(&qstash)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleBuilder {
+    destination: Option<String>,
+    cron: Option<String>,
+    settings: MessageSettings,
+}
+
+impl ScheduleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the url of the endpoint or the name of the topic the schedule publishes to.
+    pub fn destination(mut self, url_or_topic: impl Into<String>) -> Self {
+        self.destination = Some(url_or_topic.into());
+        self
+    }
+
+    /// Set the cron expression of the schedule, evaluated in UTC.
+    pub fn cron(mut self, cron: impl Into<String>) -> Self {
+        self.cron = Some(cron.into());
+        self
+    }
+
+    /// Set the number of retries of each published message.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.settings = self.settings.retries(retries);
+        self
+    }
+
+    /// Set the url QStash calls with the response of each delivery.
+    pub fn callback(mut self, callback_url: impl Into<String>) -> Self {
+        self.settings = self.settings.callback_url(callback_url);
+        self
+    }
+
+    /// Forward an HTTP header to the destination, see [`MessageSettings::forward_header`].
+    pub fn forward_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.settings = self.settings.forward_header(name, value);
+        self
+    }
+
+    /// Replace the settings of the published messages, including the ones set by
+    /// [`ScheduleBuilder::retries`], [`ScheduleBuilder::callback`] and [`ScheduleBuilder::forward_header`].
+    pub fn settings(mut self, settings: MessageSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Create the schedule with the given client.
+    ///
+    /// Fails if no destination or cron expression was set.
+    pub async fn create(self, client: &Client) -> utils::Result<ScheduleResponse> {
+        let destination = self
+            .destination
+            .ok_or(utils::QStashError::MissingDestination)?;
+        let cron = self.cron.ok_or(utils::QStashError::MissingCron)?;
+        client
+            .create_schedule(&destination, &cron, self.settings)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_durations_use_the_largest_exact_unit() {
+        let delay = |secs| {
+            MessageSettings::new()
+                .delay_duration(Duration::from_secs(secs))
+                .delay
+        };
+        assert_eq!(delay(90).as_deref(), Some("90s"));
+        assert_eq!(delay(7200).as_deref(), Some("2h"));
+        assert_eq!(delay(120).as_deref(), Some("2m"));
+        assert_eq!(delay(2 * 24 * 60 * 60).as_deref(), Some("2d"));
+        assert_eq!(delay(0).as_deref(), Some("0s"));
+    }
+
+    #[test]
+    fn delay_durations_round_fractions_of_a_second_up() {
+        let delay = |duration| MessageSettings::new().delay_duration(duration).delay;
+        assert_eq!(delay(Duration::from_millis(1)).as_deref(), Some("1s"));
+        assert_eq!(delay(Duration::from_millis(500)).as_deref(), Some("1s"));
+        assert_eq!(delay(Duration::from_millis(1500)).as_deref(), Some("2s"));
+        assert_eq!(delay(Duration::from_millis(59_500)).as_deref(), Some("1m"));
+    }
+
+    #[test]
+    fn not_before_is_sent_as_a_unix_timestamp() {
+        let headers = MessageSettings::new()
+            .not_before(1_700_000_000)
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Not-Before"], "1700000000");
+    }
+
+    #[test]
+    fn not_before_and_delay_replace_each_other() {
+        let headers = MessageSettings::new()
+            .delay("10s")
+            .not_before(1_700_000_000)
+            .as_headers()
+            .unwrap();
+        assert!(!headers.contains_key("Upstash-Delay"));
+        assert!(headers.contains_key("Upstash-Not-Before"));
+
+        let headers = MessageSettings::new()
+            .not_before(1_700_000_000)
+            .delay("10s")
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Delay"], "10s");
+        assert!(!headers.contains_key("Upstash-Not-Before"));
+    }
+
+    #[test]
+    fn callbacks_are_set_independently() {
+        let headers = MessageSettings::new()
+            .failure_callback_url("https://example.com/failure")
+            .as_headers()
+            .unwrap();
+        assert_eq!(
+            headers["Upstash-Failure-Callback"],
+            "https://example.com/failure"
+        );
+        assert!(!headers.contains_key("Upstash-Callback"));
+
+        let headers = MessageSettings::new()
+            .callback_url("https://example.com/success")
+            .failure_callback_url("https://example.com/failure")
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Callback"], "https://example.com/success");
+        assert_eq!(
+            headers["Upstash-Failure-Callback"],
+            "https://example.com/failure"
+        );
+    }
+
+    #[test]
+    fn method_is_sent_as_upstash_method() {
+        let headers = MessageSettings::new()
+            .method(Method::PUT)
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Method"], "PUT");
+        assert!(!MessageSettings::new()
+            .as_headers()
+            .unwrap()
+            .contains_key("Upstash-Method"));
+    }
+
+    #[test]
+    fn forward_headers_are_prefixed() {
+        let headers = MessageSettings::new()
+            .forward_header("X-My-Header", "value")
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Forward-X-My-Header"], "value");
+        assert!(!headers.contains_key("X-My-Header"));
+    }
+
+    #[test]
+    fn settings_own_formatted_strings() {
+        let settings: Vec<MessageSettings> = (1..=3)
+            .map(|i| {
+                MessageSettings::new()
+                    .delay(format!("{}m", i))
+                    .dedup_id(format!("order-{}", i))
+            })
+            .collect();
+
+        assert_eq!(settings[1].delay.as_deref(), Some("2m"));
+        assert_eq!(settings[2].dedup_id.as_deref(), Some("order-3"));
+    }
+
+    #[test]
+    fn default_settings_are_empty() {
+        assert_eq!(
+            format!("{:?}", MessageSettings::default()),
+            format!("{:?}", MessageSettings::new())
+        );
+        assert!(MessageSettings::default().as_headers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn content_based_dedup_is_sent_when_enabled() {
+        let headers = MessageSettings::new()
+            .content_based_dedup(true)
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Content-Based-Deduplication"], "true");
+
+        let headers = MessageSettings::new()
+            .content_based_dedup(false)
+            .as_headers()
+            .unwrap();
+        assert!(!headers.contains_key("Upstash-Content-Based-Deduplication"));
+    }
+
+    #[test]
+    fn destinations_build_their_publish_endpoints() {
+        let base = Url::parse("https://qstash.upstash.io/v2/").unwrap();
+
+        let url = Destination::Url("https://example.com/api/webhook".to_string());
+        assert_eq!(
+            url.publish_endpoint(&base).unwrap().as_str(),
+            "https://qstash.upstash.io/v2/publish/https://example.com/api/webhook"
+        );
+
+        let topic = Destination::Topic("orders".to_string());
+        assert_eq!(
+            topic.publish_endpoint(&base).unwrap().as_str(),
+            "https://qstash.upstash.io/v2/publish/orders"
+        );
+
+        let topic = Destination::Topic("orders/eu?region=1".to_string());
+        assert_eq!(
+            topic.publish_endpoint(&base).unwrap().as_str(),
+            "https://qstash.upstash.io/v2/publish/orders%2Feu%3Fregion=1"
+        );
+    }
+
+    #[test]
+    fn headers_from_parses_pairs() {
+        let settings = MessageSettings::new()
+            .headers_from([("X-Tenant", "acme"), ("X-Request-Id", "42")])
+            .unwrap();
+        let headers = settings.as_headers().unwrap();
+        assert_eq!(headers["X-Tenant"], "acme");
+        assert_eq!(headers["X-Request-Id"], "42");
+    }
+
+    #[test]
+    fn headers_from_rejects_invalid_headers() {
+        let invalid_value = MessageSettings::new().headers_from([("X-Tenant", "line\nbreak")]);
+        assert!(matches!(
+            invalid_value,
+            Err(utils::QStashError::InvalidHeaderValue(_))
+        ));
+
+        let invalid_name = MessageSettings::new().headers_from([("X Tenant", "acme")]);
+        assert!(matches!(
+            invalid_name,
+            Err(utils::QStashError::InvalidHeaderName(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_callback_urls_are_errors() {
+        let result = MessageSettings::new()
+            .callback_url("https://example.com/\ncallback")
+            .as_headers();
+        assert!(matches!(
+            result,
+            Err(utils::QStashError::InvalidHeaderValue(_))
+        ));
+    }
+
+    #[test]
+    fn flow_control_is_sent_as_key_and_value() {
+        let headers = MessageSettings::new()
+            .flow_control("tenant-42", 10, 5)
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Flow-Control-Key"], "tenant-42");
+        assert_eq!(
+            headers["Upstash-Flow-Control-Value"],
+            "rate=10, parallelism=5"
+        );
+    }
+
+    #[test]
+    fn callback_forward_headers_are_prefixed() {
+        let headers = MessageSettings::new()
+            .callback_forward_header("Authorization", "Bearer secret")
+            .as_headers()
+            .unwrap();
+        assert_eq!(
+            headers["Upstash-Callback-Forward-Authorization"],
+            "Bearer secret"
+        );
+        assert!(!headers.contains_key("Upstash-Forward-Authorization"));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn not_before_at_sends_unix_seconds() {
+        use chrono::TimeZone;
+
+        let when = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let headers = MessageSettings::new()
+            .not_before_at(when)
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Not-Before"], "1704067200");
+    }
+
+    #[test]
+    fn dedup_id_is_sent_as_deduplication_id() {
+        let headers = MessageSettings::new()
+            .dedup_id("order-42")
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Deduplication-Id"], "order-42");
+        assert!(!headers.contains_key("Upstash-Content-Based-Deduplication"));
+    }
+
+    #[test]
+    fn destination_timeout_is_sent_as_upstash_timeout() {
+        let timeout = |secs| {
+            MessageSettings::new()
+                .destination_timeout(Duration::from_secs(secs))
+                .as_headers()
+                .unwrap()["Upstash-Timeout"]
+                .clone()
+        };
+        assert_eq!(timeout(30), "30s");
+        assert_eq!(timeout(120), "2m");
+    }
 
-        headers
+    #[test]
+    fn retry_delay_is_sent_as_upstash_retry_delay() {
+        let headers = MessageSettings::new()
+            .retries(3)
+            .retry_delay("pow(2, retried) * 1000")
+            .as_headers()
+            .unwrap();
+        assert_eq!(headers["Upstash-Retry-Delay"], "pow(2, retried) * 1000");
+        assert_eq!(headers["Upstash-Retries"], "3");
+        assert!(!MessageSettings::new()
+            .as_headers()
+            .unwrap()
+            .contains_key("Upstash-Retry-Delay"));
     }
 }
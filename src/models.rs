@@ -0,0 +1,865 @@
+use crate::utils;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::HashMap;
+use url::Url;
+
+/// A point in time reported by the api.
+///
+/// Unix timestamp in milliseconds, or a `DateTime<Utc>` with the `chrono` feature.
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = i64;
+
+/// A point in time reported by the api.
+///
+/// Unix timestamp in milliseconds, or a `DateTime<Utc>` with the `chrono` feature.
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Utc>;
+
+/// Deserialize an optional unix timestamp in milliseconds into a [`Timestamp`].
+#[cfg(feature = "chrono")]
+fn timestamp_millis<'de, D>(deserializer: D) -> std::result::Result<Option<Timestamp>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<i64>::deserialize(deserializer)? {
+        Some(millis) => from_millis::<D>(millis).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Deserialize a unix timestamp in milliseconds into a [`Timestamp`].
+#[cfg(feature = "chrono")]
+fn required_timestamp_millis<'de, D>(deserializer: D) -> std::result::Result<Timestamp, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    from_millis::<D>(i64::deserialize(deserializer)?)
+}
+
+#[cfg(feature = "chrono")]
+fn from_millis<'de, D>(millis: i64) -> std::result::Result<Timestamp, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    chrono::DateTime::from_timestamp_millis(millis)
+        .ok_or_else(|| D::Error::custom(format!("timestamp out of range: {}", millis)))
+}
+
+/// Quota limits of your QStash account.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    /// The maximum number of requests allowed for the current period.
+    #[serde(default)]
+    pub max: u64,
+    /// The number of requests used in the current period.
+    #[serde(default)]
+    pub used: u64,
+}
+
+/// The delivery state of a message or task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageState {
+    Created,
+    Active,
+    Delivered,
+    Error,
+    Retry,
+    Failed,
+    Canceled,
+    /// A state not known to this version of the library.
+    Unknown(String),
+}
+
+impl<'de> Deserialize<'de> for MessageState {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let state = String::deserialize(deserializer)?;
+        Ok(match state.as_str() {
+            "CREATED" => MessageState::Created,
+            "ACTIVE" => MessageState::Active,
+            "DELIVERED" => MessageState::Delivered,
+            "ERROR" => MessageState::Error,
+            "RETRY" => MessageState::Retry,
+            "FAILED" => MessageState::Failed,
+            "CANCELED" => MessageState::Canceled,
+            _ => MessageState::Unknown(state),
+        })
+    }
+}
+
+/// A message as stored by QStash.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Message {
+    /// The id of the message.
+    pub message_id: Option<String>,
+    /// The url the message is delivered to.
+    pub url: Option<String>,
+    /// The name of the topic the message was published to, if any.
+    #[serde(alias = "topicName")]
+    pub topic: Option<String>,
+    /// The headers that are forwarded to the destination.
+    pub header: Option<HashMap<String, Vec<String>>>,
+    /// The body of the message.
+    pub body: Option<String>,
+    /// The body of the message encoded as base64, set instead of `body` for binary bodies.
+    pub body_base64: Option<String>,
+    /// When the message was created.
+    #[cfg_attr(
+        feature = "chrono",
+        serde(default, deserialize_with = "timestamp_millis")
+    )]
+    pub created_at: Option<Timestamp>,
+    /// The delivery state of the message.
+    pub state: Option<MessageState>,
+    /// The maximum number of retries for the message.
+    #[serde(alias = "maxRetry")]
+    pub max_retries: Option<u32>,
+    /// The size of the body in bytes as stored by QStash, if reported.
+    pub body_size: Option<u64>,
+    /// Whether the stored body was truncated, if reported.
+    pub truncated: Option<bool>,
+    /// The number of delivery attempts that were already retried.
+    pub retried: Option<u32>,
+    /// When the next delivery attempt is scheduled, if one is.
+    #[cfg_attr(
+        feature = "chrono",
+        serde(default, deserialize_with = "timestamp_millis")
+    )]
+    pub next_delivery: Option<Timestamp>,
+}
+
+impl Message {
+    /// The raw bytes of the body, decoded from `body_base64` when QStash returned it encoded.
+    ///
+    /// Plain bodies are returned as is, and a missing body as an empty vector.
+    pub fn decoded_body(&self) -> utils::Result<Vec<u8>> {
+        match (&self.body_base64, &self.body) {
+            (Some(encoded), _) => Ok(STANDARD.decode(encoded.trim())?),
+            (None, Some(body)) => Ok(body.as_bytes().to_vec()),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Response of publishing a message to a single destination.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishResponse {
+    /// The id of the published message.
+    pub message_id: String,
+    /// The url the message will be delivered to, set when publishing to a topic.
+    pub url: Option<String>,
+    /// Whether the message was deduplicated, in which case it was not enqueued again
+    /// and `message_id` is the id of the original message.
+    #[serde(default)]
+    pub deduplicated: bool,
+}
+
+impl PublishResponse {
+    /// Whether the deduplication id matched an already published message,
+    /// instead of being stored for a new one.
+    pub fn is_duplicate(&self) -> bool {
+        self.deduplicated
+    }
+}
+
+/// Response of the publish endpoint.
+///
+/// Publishing to a url returns a single message, while publishing to a topic
+/// returns one message per endpoint of the topic.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum PublishResponses {
+    Single(PublishResponse),
+    List(Vec<PublishResponse>),
+}
+
+impl PublishResponses {
+    /// The published messages, one per endpoint when publishing to a topic.
+    ///
+    /// Use the `url` of each response to correlate the message ids with the endpoints.
+    pub fn responses(&self) -> &[PublishResponse] {
+        match self {
+            PublishResponses::Single(response) => std::slice::from_ref(response),
+            PublishResponses::List(responses) => responses,
+        }
+    }
+
+    /// Whether any of the published messages was deduplicated.
+    pub fn is_duplicate(&self) -> bool {
+        self.responses().iter().any(PublishResponse::is_duplicate)
+    }
+
+    /// The ids of all published messages.
+    pub fn message_ids(&self) -> Vec<String> {
+        self.responses()
+            .iter()
+            .map(|response| response.message_id.clone())
+            .collect()
+    }
+}
+
+/// Response of canceling multiple messages.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CancelResponse {
+    /// The number of canceled messages.
+    #[serde(default)]
+    pub cancelled: u64,
+}
+
+/// Response of creating a schedule.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleResponse {
+    /// The id of the created schedule.
+    pub schedule_id: String,
+}
+
+/// A schedule that periodically publishes a message.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    /// The id of the schedule.
+    pub schedule_id: String,
+    /// The cron expression of the schedule.
+    pub cron: Option<String>,
+    /// The url or topic the schedule publishes to.
+    pub destination: Option<String>,
+    /// When the schedule was created.
+    #[cfg_attr(
+        feature = "chrono",
+        serde(default, deserialize_with = "timestamp_millis")
+    )]
+    pub created_at: Option<Timestamp>,
+    /// The settings forwarded with every message of the schedule.
+    pub settings: Option<Value>,
+}
+
+impl Schedule {
+    /// The next times the schedule publishes a message, in UTC.
+    ///
+    /// Returns no times if the schedule has no valid cron expression,
+    /// or one with a timezone other than UTC, as timezones are not known to this library.
+    #[cfg(feature = "cron-validation")]
+    pub fn next_runs(&self, count: usize) -> Vec<chrono::DateTime<chrono::Utc>> {
+        let cron = match &self.cron {
+            Some(cron) => cron.trim(),
+            None => return Vec::new(),
+        };
+        if let Some(rest) = cron.strip_prefix("CRON_TZ=") {
+            if !rest.starts_with("UTC ") && !rest.starts_with("Etc/UTC ") {
+                return Vec::new();
+            }
+        }
+
+        match crate::utils::parse_cron(cron) {
+            Ok(schedule) => schedule.upcoming(chrono::Utc).take(count).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+/// A topic that delivers messages to all of its endpoints.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Topic {
+    /// The name of the topic.
+    pub name: String,
+    /// The endpoints messages are delivered to.
+    #[serde(default)]
+    pub endpoints: Vec<Endpoint>,
+}
+
+/// An endpoint of a topic.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    /// The name of the endpoint.
+    pub name: Option<String>,
+    /// The url messages are delivered to.
+    pub url: String,
+}
+
+/// A message that could not be delivered and was moved to the dead letter queue.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DlqMessage {
+    /// The id of the message in the dead letter queue.
+    pub dlq_id: Option<String>,
+    /// The id of the original message.
+    pub message_id: String,
+    /// The url the message was delivered to.
+    pub url: Option<String>,
+    /// The http status of the last delivery attempt.
+    pub response_status: Option<u16>,
+    /// The response body of the last delivery attempt.
+    pub response_body: Option<String>,
+}
+
+/// A page of messages in the dead letter queue.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DlqList {
+    /// Cursor for the next page, `None` when there are no more messages.
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub messages: Vec<DlqMessage>,
+}
+
+/// Filter for listing events, unset fields are not filtered on.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    /// Only events of the message with this id.
+    pub message_id: Option<String>,
+    /// Only events with this state, e.g. `DELIVERED`.
+    pub state: Option<String>,
+    /// Only events of messages published to this topic.
+    pub topic: Option<String>,
+    /// Only events of messages created by the schedule with this id.
+    ///
+    /// The api has no endpoint listing messages, use this to trace the messages of a schedule.
+    pub schedule_id: Option<String>,
+    /// Only events after this unix timestamp in milliseconds.
+    pub from: Option<i64>,
+    /// Only events before this unix timestamp in milliseconds.
+    pub to: Option<i64>,
+    /// Cursor of the page to fetch.
+    pub cursor: Option<String>,
+}
+
+impl EventFilter {
+    /// Append the set filters to the query string of the url.
+    pub(crate) fn apply(&self, url: &mut Url) {
+        let pairs = [
+            ("messageId", self.message_id.clone()),
+            ("state", self.state.clone()),
+            ("topicName", self.topic.clone()),
+            ("scheduleId", self.schedule_id.clone()),
+            ("fromDate", self.from.map(|from| from.to_string())),
+            ("toDate", self.to.map(|to| to.to_string())),
+            ("cursor", self.cursor.clone()),
+        ];
+
+        let mut pairs = pairs
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|value| (name, value)))
+            .peekable();
+        if pairs.peek().is_some() {
+            url.query_pairs_mut().extend_pairs(pairs);
+        }
+    }
+}
+
+/// A state transition in the lifecycle of a message.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Event {
+    /// When the event happened.
+    #[cfg_attr(
+        feature = "chrono",
+        serde(deserialize_with = "required_timestamp_millis")
+    )]
+    pub time: Timestamp,
+    /// The state the message transitioned to.
+    pub state: Option<MessageState>,
+    /// The id of the message.
+    pub message_id: Option<String>,
+    /// The url the message is delivered to.
+    pub url: Option<String>,
+    /// The error of the delivery attempt, if it failed.
+    pub error: Option<String>,
+    /// The http status the destination responded with.
+    pub response_status: Option<u16>,
+    /// The headers the destination responded with.
+    #[serde(alias = "responseHeaders")]
+    pub response_header: Option<HashMap<String, Vec<String>>>,
+    /// The body the destination responded with.
+    pub response_body: Option<String>,
+}
+
+impl Event {
+    /// The response of the destination to the delivery attempt, if QStash recorded one.
+    pub fn response(&self) -> Option<DeliveryResponse> {
+        Some(DeliveryResponse {
+            status: self.response_status?,
+            header: self.response_header.clone().unwrap_or_default(),
+            body: self.response_body.clone().unwrap_or_default(),
+        })
+    }
+}
+
+/// The response of a destination to a delivery attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryResponse {
+    /// The http status of the response.
+    pub status: u16,
+    /// The headers of the response.
+    pub header: HashMap<String, Vec<String>>,
+    /// The body of the response.
+    pub body: String,
+}
+
+/// A page of events.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventList {
+    /// Cursor for the next page, `None` when there are no more events.
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+}
+
+/// The keys QStash uses to sign the messages it delivers.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningKeys {
+    /// The key currently used to sign messages.
+    pub current: String,
+    /// The key that will be used after the next rotation.
+    pub next: String,
+}
+
+/// Rate limit information returned by the api with publish requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of requests in the current window.
+    pub limit: u64,
+    /// The number of requests remaining in the current window.
+    pub remaining: u64,
+    /// Unix timestamp in seconds of when the window resets.
+    pub reset: u64,
+}
+
+impl RateLimit {
+    /// Read the `RateLimit-*` headers of a response, `None` if any of them is missing.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+        };
+
+        Some(Self {
+            limit: value("RateLimit-Limit")?,
+            remaining: value("RateLimit-Remaining")?,
+            reset: value("RateLimit-Reset")?,
+        })
+    }
+}
+
+/// A queue that delivers its messages in order, with limited parallelism.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Queue {
+    /// The name of the queue.
+    pub name: String,
+    /// The number of messages delivered in parallel.
+    #[serde(default)]
+    pub parallelism: u32,
+    /// The number of messages waiting to be delivered.
+    pub lag: Option<u64>,
+    /// When the queue was created.
+    #[cfg_attr(
+        feature = "chrono",
+        serde(default, deserialize_with = "timestamp_millis")
+    )]
+    pub created_at: Option<Timestamp>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The unix timestamp in milliseconds, with or without the `chrono` feature.
+    fn millis(timestamp: Timestamp) -> i64 {
+        #[cfg(feature = "chrono")]
+        let timestamp = timestamp.timestamp_millis();
+        timestamp
+    }
+
+    #[test]
+    fn quota_deserializes_and_tolerates_missing_fields() {
+        let quota: Quota =
+            serde_json::from_value(json!({ "max": 500, "used": 42, "plan": "free" })).unwrap();
+        assert_eq!(quota.max, 500);
+        assert_eq!(quota.used, 42);
+
+        let quota: Quota = serde_json::from_value(json!({})).unwrap();
+        assert_eq!((quota.max, quota.used), (0, 0));
+    }
+
+    #[test]
+    fn message_deserializes_a_representative_payload() {
+        let message: Message = serde_json::from_value(json!({
+            "messageId": "msg_123",
+            "url": "https://example.com/api/webhook",
+            "topicName": "orders",
+            "header": { "Content-Type": ["application/json"] },
+            "body": "{\"key\":\"value\"}",
+            "createdAt": 1_700_000_000_000_i64,
+            "state": "DELIVERED",
+            "maxRetry": 3,
+            "somethingNew": true
+        }))
+        .unwrap();
+
+        assert_eq!(message.message_id.as_deref(), Some("msg_123"));
+        assert_eq!(
+            message.url.as_deref(),
+            Some("https://example.com/api/webhook")
+        );
+        assert_eq!(message.topic.as_deref(), Some("orders"));
+        assert_eq!(
+            message.header.unwrap()["Content-Type"],
+            vec!["application/json".to_string()]
+        );
+        assert_eq!(message.body.as_deref(), Some("{\"key\":\"value\"}"));
+        assert!(message.created_at.is_some());
+        assert_eq!(message.state, Some(MessageState::Delivered));
+        assert_eq!(message.max_retries, Some(3));
+    }
+
+    #[test]
+    fn publish_responses_deserialize_single_and_topic_responses() {
+        let single: PublishResponses =
+            serde_json::from_value(json!({ "messageId": "msg_1" })).unwrap();
+        assert!(matches!(single, PublishResponses::Single(_)));
+        assert_eq!(single.message_ids(), vec!["msg_1".to_string()]);
+
+        let list: PublishResponses = serde_json::from_value(json!([
+            { "messageId": "msg_1", "url": "https://a.example.com" },
+            { "messageId": "msg_2", "url": "https://b.example.com" }
+        ]))
+        .unwrap();
+        match &list {
+            PublishResponses::List(responses) => {
+                assert_eq!(responses[1].url.as_deref(), Some("https://b.example.com"));
+            }
+            PublishResponses::Single(_) => panic!("expected a list of responses"),
+        }
+        assert_eq!(
+            list.message_ids(),
+            vec!["msg_1".to_string(), "msg_2".to_string()]
+        );
+    }
+
+    #[test]
+    fn event_filter_only_queries_set_filters() {
+        let mut url = Url::parse("https://qstash.upstash.io/v2/events").unwrap();
+        EventFilter::default().apply(&mut url);
+        assert_eq!(url.query(), None);
+
+        let filter = EventFilter {
+            message_id: Some("msg_1".to_string()),
+            state: Some("DELIVERED".to_string()),
+            topic: Some("orders & more".to_string()),
+            from: Some(1_700_000_000_000),
+            cursor: Some("page-2".to_string()),
+            ..Default::default()
+        };
+        filter.apply(&mut url);
+        assert_eq!(
+            url.query(),
+            Some("messageId=msg_1&state=DELIVERED&topicName=orders+%26+more&fromDate=1700000000000&cursor=page-2")
+        );
+    }
+
+    #[test]
+    fn event_list_deserializes_events() {
+        let list: EventList = serde_json::from_value(json!({
+            "cursor": "page-2",
+            "events": [{
+                "time": 1_700_000_000_000_i64,
+                "state": "ERROR",
+                "messageId": "msg_1",
+                "url": "https://example.com/api/webhook",
+                "error": "connection refused"
+            }, {
+                "time": 1_700_000_000_001_i64
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(list.cursor.as_deref(), Some("page-2"));
+        let event = &list.events[0];
+        assert_eq!(millis(event.time), 1_700_000_000_000);
+        assert_eq!(event.state, Some(MessageState::Error));
+        assert_eq!(event.message_id.as_deref(), Some("msg_1"));
+        assert_eq!(
+            event.url.as_deref(),
+            Some("https://example.com/api/webhook")
+        );
+        assert_eq!(event.error.as_deref(), Some("connection refused"));
+        assert!(list.events[1].state.is_none());
+    }
+
+    #[test]
+    fn signing_keys_deserialize() {
+        let keys: SigningKeys =
+            serde_json::from_value(json!({ "current": "sig_current", "next": "sig_next" }))
+                .unwrap();
+        assert_eq!(keys.current, "sig_current");
+        assert_eq!(keys.next, "sig_next");
+    }
+
+    #[test]
+    fn message_states_deserialize() {
+        let states = [
+            ("CREATED", MessageState::Created),
+            ("ACTIVE", MessageState::Active),
+            ("DELIVERED", MessageState::Delivered),
+            ("ERROR", MessageState::Error),
+            ("RETRY", MessageState::Retry),
+            ("FAILED", MessageState::Failed),
+            ("CANCELED", MessageState::Canceled),
+            (
+                "IN_PROGRESS",
+                MessageState::Unknown("IN_PROGRESS".to_string()),
+            ),
+        ];
+        for (name, state) in states {
+            assert_eq!(
+                serde_json::from_value::<MessageState>(json!(name)).unwrap(),
+                state
+            );
+        }
+    }
+
+    #[test]
+    fn publish_responses_flag_deduplicated_messages() {
+        let response: PublishResponse =
+            serde_json::from_value(json!({ "messageId": "msg_1", "deduplicated": true })).unwrap();
+        assert!(response.is_duplicate());
+
+        let response: PublishResponse =
+            serde_json::from_value(json!({ "messageId": "msg_2" })).unwrap();
+        assert!(!response.is_duplicate());
+    }
+
+    #[test]
+    fn topic_publish_responses_pair_ids_with_urls() {
+        let response: PublishResponses = serde_json::from_value(json!([
+            { "messageId": "msg_1", "url": "https://a.example.com" },
+            { "messageId": "msg_2", "url": "https://b.example.com" },
+            { "messageId": "msg_3", "url": "https://c.example.com" }
+        ]))
+        .unwrap();
+
+        assert!(matches!(response, PublishResponses::List(_)));
+        let pairs: Vec<_> = response
+            .responses()
+            .iter()
+            .map(|response| (response.message_id.as_str(), response.url.as_deref()))
+            .collect();
+        assert_eq!(
+            pairs,
+            [
+                ("msg_1", Some("https://a.example.com")),
+                ("msg_2", Some("https://b.example.com")),
+                ("msg_3", Some("https://c.example.com")),
+            ]
+        );
+    }
+
+    #[test]
+    fn message_body_size_and_truncation_are_optional() {
+        let message: Message = serde_json::from_value(json!({
+            "messageId": "msg_1",
+            "bodySize": 2048,
+            "truncated": true
+        }))
+        .unwrap();
+        assert_eq!(message.body_size, Some(2048));
+        assert_eq!(message.truncated, Some(true));
+
+        let message: Message = serde_json::from_value(json!({ "messageId": "msg_1" })).unwrap();
+        assert_eq!(message.body_size, None);
+        assert_eq!(message.truncated, None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn timestamps_are_converted_from_milliseconds() {
+        use chrono::TimeZone;
+
+        let expected = chrono::Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+            + chrono::Duration::milliseconds(123);
+        let millis = json!(1_704_067_200_123_i64);
+
+        let message: Message =
+            serde_json::from_value(json!({ "messageId": "msg_1", "createdAt": millis })).unwrap();
+        assert_eq!(message.created_at, Some(expected));
+        let schedule: Schedule =
+            serde_json::from_value(json!({ "scheduleId": "scd_1", "createdAt": millis })).unwrap();
+        assert_eq!(schedule.created_at, Some(expected));
+        let queue: Queue =
+            serde_json::from_value(json!({ "name": "orders", "createdAt": millis })).unwrap();
+        assert_eq!(queue.created_at, Some(expected));
+        let event: Event = serde_json::from_value(json!({ "time": millis })).unwrap();
+        assert_eq!(event.time, expected);
+        let message: Message =
+            serde_json::from_value(json!({ "messageId": "msg_1", "nextDelivery": millis }))
+                .unwrap();
+        assert_eq!(message.next_delivery, Some(expected));
+
+        let message: Message = serde_json::from_value(json!({ "messageId": "msg_1" })).unwrap();
+        assert_eq!(message.created_at, None);
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    #[test]
+    fn timestamps_are_milliseconds_without_chrono() {
+        let message: Message = serde_json::from_value(
+            json!({ "messageId": "msg_1", "createdAt": 1_704_067_200_123_i64 }),
+        )
+        .unwrap();
+        assert_eq!(message.created_at, Some(1_704_067_200_123));
+    }
+
+    #[test]
+    fn message_retry_progress_deserializes() {
+        let message: Message = serde_json::from_value(json!({
+            "messageId": "msg_1",
+            "retried": 2,
+            "nextDelivery": 1_700_000_060_000_i64
+        }))
+        .unwrap();
+        assert_eq!(message.retried, Some(2));
+        assert_eq!(message.next_delivery.map(millis), Some(1_700_000_060_000));
+    }
+
+    #[cfg(feature = "cron-validation")]
+    fn schedule(cron: &str) -> Schedule {
+        serde_json::from_value(json!({ "scheduleId": "scd_1", "cron": cron })).unwrap()
+    }
+
+    #[cfg(feature = "cron-validation")]
+    #[test]
+    fn next_runs_of_an_hourly_schedule() {
+        use chrono::Timelike;
+
+        let runs = schedule("0 * * * *").next_runs(3);
+        assert_eq!(runs.len(), 3);
+        assert!(runs
+            .iter()
+            .all(|run| run.minute() == 0 && run.second() == 0));
+        assert!(runs[0] > chrono::Utc::now());
+        assert_eq!(runs[1] - runs[0], chrono::Duration::hours(1));
+        assert_eq!(runs[2] - runs[1], chrono::Duration::hours(1));
+    }
+
+    #[cfg(feature = "cron-validation")]
+    #[test]
+    fn next_runs_count_days_of_week_from_sunday() {
+        use chrono::{Datelike, Timelike, Weekday};
+
+        let runs = schedule("0 9 * * 1").next_runs(2);
+        assert_eq!(runs.len(), 2);
+        assert!(runs
+            .iter()
+            .all(|run| run.weekday() == Weekday::Mon && run.hour() == 9));
+
+        for cron in ["0 0 * * 0", "0 0 * * 7"] {
+            let runs = schedule(cron).next_runs(2);
+            assert_eq!(runs.len(), 2, "{}", cron);
+            assert!(
+                runs.iter().all(|run| run.weekday() == Weekday::Sun),
+                "{}",
+                cron
+            );
+        }
+
+        let runs = schedule("0 0 * * 5-7").next_runs(3);
+        let days: Vec<_> = runs.iter().map(|run| run.weekday()).collect();
+        assert!(days
+            .iter()
+            .all(|day| [Weekday::Fri, Weekday::Sat, Weekday::Sun].contains(day)));
+    }
+
+    #[cfg(feature = "cron-validation")]
+    #[test]
+    fn next_runs_are_empty_for_invalid_or_non_utc_schedules() {
+        assert!(schedule("not a cron").next_runs(3).is_empty());
+        assert!(schedule("CRON_TZ=Europe/Berlin 0 9 * * 1")
+            .next_runs(3)
+            .is_empty());
+        assert_eq!(schedule("CRON_TZ=UTC 0 9 * * 1").next_runs(3).len(), 3);
+    }
+
+    #[test]
+    fn publish_responses_are_duplicates_if_any_message_was_deduplicated() {
+        let fresh: PublishResponses = serde_json::from_value(json!([
+            { "messageId": "msg_1", "url": "https://a.example.com" },
+            { "messageId": "msg_2", "url": "https://b.example.com" }
+        ]))
+        .unwrap();
+        assert!(!fresh.is_duplicate());
+
+        let duplicate: PublishResponses = serde_json::from_value(json!([
+            { "messageId": "msg_1", "url": "https://a.example.com", "deduplicated": true },
+            { "messageId": "msg_2", "url": "https://b.example.com" }
+        ]))
+        .unwrap();
+        assert!(duplicate.is_duplicate());
+    }
+
+    #[test]
+    fn events_expose_the_response_of_the_destination() {
+        let event: Event = serde_json::from_value(json!({
+            "time": 1,
+            "state": "ERROR",
+            "responseStatus": 503,
+            "responseHeader": { "Retry-After": ["30"] },
+            "responseBody": "service unavailable"
+        }))
+        .unwrap();
+        assert_eq!(
+            event.response(),
+            Some(DeliveryResponse {
+                status: 503,
+                header: HashMap::from([("Retry-After".to_string(), vec!["30".to_string()])]),
+                body: "service unavailable".to_string(),
+            })
+        );
+
+        let event: Event =
+            serde_json::from_value(json!({ "time": 1, "state": "CREATED" })).unwrap();
+        assert_eq!(event.response(), None);
+    }
+
+    #[test]
+    fn decoded_body_handles_encoded_and_plain_bodies() {
+        let encoded: Message = serde_json::from_value(json!({
+            "messageId": "msg_1",
+            "bodyBase64": "aGVsbG8gd29ybGQ="
+        }))
+        .unwrap();
+        assert_eq!(encoded.decoded_body().unwrap(), b"hello world");
+
+        let plain: Message =
+            serde_json::from_value(json!({ "messageId": "msg_1", "body": "hello world" })).unwrap();
+        assert_eq!(plain.decoded_body().unwrap(), b"hello world");
+
+        let empty: Message = serde_json::from_value(json!({ "messageId": "msg_1" })).unwrap();
+        assert!(empty.decoded_body().unwrap().is_empty());
+
+        let invalid: Message =
+            serde_json::from_value(json!({ "messageId": "msg_1", "bodyBase64": "not base64!" }))
+                .unwrap();
+        assert!(matches!(
+            invalid.decoded_body(),
+            Err(utils::QStashError::InvalidBase64(_))
+        ));
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+/// A QStash pricing plan, see <https://upstash.com/pricing/qstash>.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    Free,
+    PayAsYouGo,
+    Pro,
+}
+
+impl Plan {
+    /// The limits of messages published with this plan.
+    pub fn limits(self) -> PlanLimits {
+        match self {
+            Plan::Free => PlanLimits::FREE,
+            Plan::PayAsYouGo => PlanLimits::PAY_AS_YOU_GO,
+            Plan::Pro => PlanLimits::PRO,
+        }
+    }
+}
+
+/// Limits of published messages that depend on the plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlanLimits {
+    /// Maximum size of a message body in bytes.
+    pub max_body_size: usize,
+    /// Maximum number of retries of a message.
+    pub max_retries: u32,
+    /// Maximum delay of a message, `None` if the delay is not limited.
+    pub max_delay: Option<Duration>,
+}
+
+impl PlanLimits {
+    pub const FREE: PlanLimits = PlanLimits {
+        max_body_size: 1_000_000,
+        max_retries: 3,
+        max_delay: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+    };
+
+    pub const PAY_AS_YOU_GO: PlanLimits = PlanLimits {
+        max_body_size: 1_000_000,
+        max_retries: 5,
+        max_delay: Some(Duration::from_secs(365 * 24 * 60 * 60)),
+    };
+
+    pub const PRO: PlanLimits = PlanLimits {
+        max_body_size: 10_000_000,
+        max_retries: 20,
+        max_delay: None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plan_limits() {
+        assert_eq!(Plan::Free.limits(), PlanLimits::FREE);
+        assert_eq!(PlanLimits::FREE.max_body_size, 1_000_000);
+        assert_eq!(PlanLimits::FREE.max_retries, 3);
+        assert_eq!(
+            PlanLimits::FREE.max_delay,
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+
+        assert_eq!(Plan::PayAsYouGo.limits(), PlanLimits::PAY_AS_YOU_GO);
+        assert_eq!(PlanLimits::PAY_AS_YOU_GO.max_retries, 5);
+
+        assert_eq!(Plan::Pro.limits(), PlanLimits::PRO);
+        assert_eq!(PlanLimits::PRO.max_body_size, 10_000_000);
+        assert_eq!(PlanLimits::PRO.max_delay, None);
+    }
+
+    #[test]
+    fn clients_report_the_limits_of_their_plan() {
+        let client = crate::Client::builder()
+            .token("token")
+            .plan(Plan::Free)
+            .build()
+            .unwrap();
+        assert_eq!(client.plan_limits(), Some(PlanLimits::FREE));
+        assert_eq!(crate::Client::new("token").unwrap().plan_limits(), None);
+    }
+}
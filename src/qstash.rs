@@ -0,0 +1,418 @@
+use crate::{
+    BatchItem, Client, DeliveryResponse, Destination, DlqList, DlqMessage, Event, EventFilter,
+    EventList, Message, MessageSettings, PublishResponse, PublishResponses, Queue, Quota, Result,
+    Schedule, ScheduleResponse, SigningKeys, Topic,
+};
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::future::Future;
+
+/// The QStash api, implemented by [`Client`].
+///
+/// Depend on this trait instead of the [`Client`] to replace it with a mock in tests,
+/// e.g. one generated with `mockall`. See the [`Client`] for the documentation of each method.
+///
+/// Not available on wasm32, where the futures of the client are not `Send`.
+///
+/// # Example
+///
+/// ```no_run
+/// use upstash_qstash::QStash;
+///
+/// async fn notify(qstash: &impl QStash, url: &str) -> upstash_qstash::Result<()> {
+///     qstash
+///         .publish_json(url, &serde_json::json!({ "event": "signup" }), None)
+///         .await?;
+///     Ok(())
+/// }
+/// ```
+pub trait QStash {
+    fn raw_get(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<(StatusCode, HeaderMap, Bytes)>> + Send;
+
+    fn get_quota(&self) -> impl Future<Output = Result<Quota>> + Send;
+
+    fn get_message(&self, message_id: &str) -> impl Future<Output = Result<Message>> + Send;
+
+    fn get_messages(
+        &self,
+        message_ids: &[&str],
+    ) -> impl Future<Output = Result<Vec<Result<Message>>>> + Send;
+
+    fn get_message_raw(&self, message_id: &str) -> impl Future<Output = Result<Value>> + Send;
+
+    fn get_message_body<T: DeserializeOwned + Send>(
+        &self,
+        message_id: &str,
+    ) -> impl Future<Output = Result<T>> + Send;
+
+    fn publish_json<T: Serialize + Sync>(
+        &self,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send;
+
+    fn publish<T: Serialize + Sync>(
+        &self,
+        destination: Destination,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send;
+
+    fn publish_fanout<T: Serialize + Sync>(
+        &self,
+        urls: &[&str],
+        body: &T,
+        message_settings: Option<MessageSettings>,
+        concurrency: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<Result<PublishResponse>>>> + Send;
+
+    fn publish_bytes(
+        &self,
+        url_or_topic: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send;
+
+    fn publish_form(
+        &self,
+        url_or_topic: &str,
+        form: &[(&str, &str)],
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send;
+
+    fn publish_batch(
+        &self,
+        items: Vec<BatchItem>,
+    ) -> impl Future<Output = Result<Vec<PublishResponse>>> + Send;
+
+    fn check_destination(&self, url: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    fn create_schedule(
+        &self,
+        destination: &str,
+        cron: &str,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<ScheduleResponse>> + Send;
+
+    fn list_schedules(&self) -> impl Future<Output = Result<Vec<Schedule>>> + Send;
+
+    fn get_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<Schedule>> + Send;
+
+    fn delete_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn pause_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn resume_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn list_topics(&self) -> impl Future<Output = Result<Vec<Topic>>> + Send;
+
+    fn delete_topic(&self, name: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn add_endpoint(
+        &self,
+        topic: &str,
+        name: &str,
+        url: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn remove_endpoint(
+        &self,
+        topic: &str,
+        endpoint_name: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn create_queue(&self, name: &str, parallelism: u32)
+        -> impl Future<Output = Result<()>> + Send;
+
+    fn list_queues(&self) -> impl Future<Output = Result<Vec<Queue>>> + Send;
+
+    fn delete_queue(&self, name: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn publish_to_queue<T: Serialize + Sync>(
+        &self,
+        queue: &str,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send;
+
+    fn cancel_message(&self, message_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn cancel_messages(&self, message_ids: &[&str]) -> impl Future<Output = Result<()>> + Send;
+
+    fn cancel_by_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<u64>> + Send;
+
+    fn dlq_list(&self, cursor: Option<&str>) -> impl Future<Output = Result<DlqList>> + Send;
+
+    fn dlq_list_all(&self, limit: usize) -> impl Future<Output = Result<Vec<DlqMessage>>> + Send;
+
+    fn dlq_get(&self, dlq_message_id: &str) -> impl Future<Output = Result<DlqMessage>> + Send;
+
+    fn dlq_delete(&self, dlq_message_id: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn list_events(&self, filter: EventFilter) -> impl Future<Output = Result<EventList>> + Send;
+
+    fn get_message_response(
+        &self,
+        message_id: &str,
+    ) -> impl Future<Output = Result<DeliveryResponse>> + Send;
+
+    fn list_events_all(
+        &self,
+        filter: EventFilter,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Event>>> + Send;
+
+    fn schedule_messages(
+        &self,
+        schedule_id: &str,
+        cursor: Option<&str>,
+    ) -> impl Future<Output = Result<EventList>> + Send;
+
+    fn get_signing_keys(&self) -> impl Future<Output = Result<SigningKeys>> + Send;
+
+    fn rotate_signing_keys(&self) -> impl Future<Output = Result<SigningKeys>> + Send;
+}
+
+impl QStash for Client {
+    fn raw_get(
+        &self,
+        path: &str,
+    ) -> impl Future<Output = Result<(StatusCode, HeaderMap, Bytes)>> + Send {
+        Client::raw_get(self, path)
+    }
+
+    fn get_quota(&self) -> impl Future<Output = Result<Quota>> + Send {
+        Client::get_quota(self)
+    }
+
+    fn get_message(&self, message_id: &str) -> impl Future<Output = Result<Message>> + Send {
+        Client::get_message(self, message_id)
+    }
+
+    fn get_messages(
+        &self,
+        message_ids: &[&str],
+    ) -> impl Future<Output = Result<Vec<Result<Message>>>> + Send {
+        Client::get_messages(self, message_ids)
+    }
+
+    fn get_message_raw(&self, message_id: &str) -> impl Future<Output = Result<Value>> + Send {
+        Client::get_message_raw(self, message_id)
+    }
+
+    fn get_message_body<T: DeserializeOwned + Send>(
+        &self,
+        message_id: &str,
+    ) -> impl Future<Output = Result<T>> + Send {
+        Client::get_message_body(self, message_id)
+    }
+
+    fn publish_json<T: Serialize + Sync>(
+        &self,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send {
+        Client::publish_json(self, url_or_topic, body, message_settings)
+    }
+
+    fn publish<T: Serialize + Sync>(
+        &self,
+        destination: Destination,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send {
+        Client::publish(self, destination, body, message_settings)
+    }
+
+    fn publish_fanout<T: Serialize + Sync>(
+        &self,
+        urls: &[&str],
+        body: &T,
+        message_settings: Option<MessageSettings>,
+        concurrency: Option<usize>,
+    ) -> impl Future<Output = Result<Vec<Result<PublishResponse>>>> + Send {
+        Client::publish_fanout(self, urls, body, message_settings, concurrency)
+    }
+
+    fn publish_bytes(
+        &self,
+        url_or_topic: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send {
+        Client::publish_bytes(self, url_or_topic, body, content_type, message_settings)
+    }
+
+    fn publish_form(
+        &self,
+        url_or_topic: &str,
+        form: &[(&str, &str)],
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send {
+        Client::publish_form(self, url_or_topic, form, message_settings)
+    }
+
+    fn publish_batch(
+        &self,
+        items: Vec<BatchItem>,
+    ) -> impl Future<Output = Result<Vec<PublishResponse>>> + Send {
+        Client::publish_batch(self, items)
+    }
+
+    fn check_destination(&self, url: &str) -> impl Future<Output = Result<bool>> + Send {
+        Client::check_destination(self, url)
+    }
+
+    fn create_schedule(
+        &self,
+        destination: &str,
+        cron: &str,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<ScheduleResponse>> + Send {
+        Client::create_schedule(self, destination, cron, message_settings)
+    }
+
+    fn list_schedules(&self) -> impl Future<Output = Result<Vec<Schedule>>> + Send {
+        Client::list_schedules(self)
+    }
+
+    fn get_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<Schedule>> + Send {
+        Client::get_schedule(self, schedule_id)
+    }
+
+    fn delete_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::delete_schedule(self, schedule_id)
+    }
+
+    fn pause_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::pause_schedule(self, schedule_id)
+    }
+
+    fn resume_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::resume_schedule(self, schedule_id)
+    }
+
+    fn list_topics(&self) -> impl Future<Output = Result<Vec<Topic>>> + Send {
+        Client::list_topics(self)
+    }
+
+    fn delete_topic(&self, name: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::delete_topic(self, name)
+    }
+
+    fn add_endpoint(
+        &self,
+        topic: &str,
+        name: &str,
+        url: &str,
+    ) -> impl Future<Output = Result<()>> + Send {
+        Client::add_endpoint(self, topic, name, url)
+    }
+
+    fn remove_endpoint(
+        &self,
+        topic: &str,
+        endpoint_name: &str,
+    ) -> impl Future<Output = Result<()>> + Send {
+        Client::remove_endpoint(self, topic, endpoint_name)
+    }
+
+    fn create_queue(
+        &self,
+        name: &str,
+        parallelism: u32,
+    ) -> impl Future<Output = Result<()>> + Send {
+        Client::create_queue(self, name, parallelism)
+    }
+
+    fn list_queues(&self) -> impl Future<Output = Result<Vec<Queue>>> + Send {
+        Client::list_queues(self)
+    }
+
+    fn delete_queue(&self, name: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::delete_queue(self, name)
+    }
+
+    fn publish_to_queue<T: Serialize + Sync>(
+        &self,
+        queue: &str,
+        url_or_topic: &str,
+        body: &T,
+        message_settings: Option<MessageSettings>,
+    ) -> impl Future<Output = Result<PublishResponses>> + Send {
+        Client::publish_to_queue(self, queue, url_or_topic, body, message_settings)
+    }
+
+    fn cancel_message(&self, message_id: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::cancel_message(self, message_id)
+    }
+
+    fn cancel_messages(&self, message_ids: &[&str]) -> impl Future<Output = Result<()>> + Send {
+        Client::cancel_messages(self, message_ids)
+    }
+
+    fn cancel_by_schedule(&self, schedule_id: &str) -> impl Future<Output = Result<u64>> + Send {
+        Client::cancel_by_schedule(self, schedule_id)
+    }
+
+    fn dlq_list(&self, cursor: Option<&str>) -> impl Future<Output = Result<DlqList>> + Send {
+        Client::dlq_list(self, cursor)
+    }
+
+    fn dlq_list_all(&self, limit: usize) -> impl Future<Output = Result<Vec<DlqMessage>>> + Send {
+        Client::dlq_list_all(self, limit)
+    }
+
+    fn dlq_get(&self, dlq_message_id: &str) -> impl Future<Output = Result<DlqMessage>> + Send {
+        Client::dlq_get(self, dlq_message_id)
+    }
+
+    fn dlq_delete(&self, dlq_message_id: &str) -> impl Future<Output = Result<()>> + Send {
+        Client::dlq_delete(self, dlq_message_id)
+    }
+
+    fn list_events(&self, filter: EventFilter) -> impl Future<Output = Result<EventList>> + Send {
+        Client::list_events(self, filter)
+    }
+
+    fn get_message_response(
+        &self,
+        message_id: &str,
+    ) -> impl Future<Output = Result<DeliveryResponse>> + Send {
+        Client::get_message_response(self, message_id)
+    }
+
+    fn list_events_all(
+        &self,
+        filter: EventFilter,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<Event>>> + Send {
+        Client::list_events_all(self, filter, limit)
+    }
+
+    fn schedule_messages(
+        &self,
+        schedule_id: &str,
+        cursor: Option<&str>,
+    ) -> impl Future<Output = Result<EventList>> + Send {
+        Client::schedule_messages(self, schedule_id, cursor)
+    }
+
+    fn get_signing_keys(&self) -> impl Future<Output = Result<SigningKeys>> + Send {
+        Client::get_signing_keys(self)
+    }
+
+    fn rotate_signing_keys(&self) -> impl Future<Output = Result<SigningKeys>> + Send {
+        Client::rotate_signing_keys(self)
+    }
+}
@@ -0,0 +1,67 @@
+/// A region of the QStash api server.
+///
+/// QStash has no header to pin messages to a region, messages are stored and delivered
+/// in the region of the api server they were published to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Region {
+    /// Frankfurt, the region of the default api server.
+    EuCentral1,
+    /// North Virginia.
+    UsEast1,
+    /// Any other region by its name, e.g. `ap-southeast-1`.
+    Other(String),
+}
+
+impl Region {
+    /// The base url of the api server of this region.
+    pub fn base_url(&self) -> String {
+        match self {
+            Region::EuCentral1 => "https://qstash.upstash.io/v2/".to_string(),
+            Region::UsEast1 => "https://qstash-us-east-1.upstash.io/v2/".to_string(),
+            Region::Other(name) => format!("https://qstash-{}.upstash.io/v2/", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(region: Region) -> String {
+        let client = crate::Client::builder()
+            .token("token")
+            .region(region)
+            .build()
+            .unwrap();
+        client
+            .endpoint("quota")
+            .unwrap()
+            .host_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn requests_are_sent_to_the_api_server_of_the_region() {
+        assert_eq!(host(Region::EuCentral1), "qstash.upstash.io");
+        assert_eq!(host(Region::UsEast1), "qstash-us-east-1.upstash.io");
+        assert_eq!(
+            host(Region::Other("ap-southeast-1".to_string())),
+            "qstash-ap-southeast-1.upstash.io"
+        );
+    }
+
+    #[test]
+    fn a_base_url_takes_precedence_over_the_region() {
+        let client = crate::Client::builder()
+            .token("token")
+            .region(Region::UsEast1)
+            .base_url("http://localhost:8080/v2/")
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.endpoint("quota").unwrap().as_str(),
+            "http://localhost:8080/v2/quota"
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use crate::{retry, utils, Client, RateLimit};
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::StatusCode;
+use reqwest::{header, Method, RequestBuilder, Response, Url};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Characters of a destination that are percent-encoded in the path,
+/// so QStash receives the query and fragment of the url as part of the destination.
+const DESTINATION: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'&')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Append a destination url or topic name to the path of an endpoint.
+///
+/// `Url::join` would parse the query and fragment of a destination url
+/// as the query and fragment of the api url, so the path is extended instead.
+/// Destinations with dot segments are rejected, as they would be resolved to another endpoint.
+pub(crate) fn append_destination(mut endpoint: Url, destination: &str) -> utils::Result<Url> {
+    check_segments(destination.split(['/', '\\']))?;
+
+    let path = format!(
+        "{}{}",
+        endpoint.path(),
+        utf8_percent_encode(destination, DESTINATION)
+    );
+    endpoint.set_path(&path);
+    Ok(endpoint)
+}
+
+/// Reject dot segments, which would be resolved to the parent path instead of kept as is.
+pub(crate) fn check_segments<'a>(mut segments: impl Iterator<Item = &'a str>) -> utils::Result<()> {
+    match segments.find(|segment| matches!(*segment, "." | "..")) {
+        Some(segment) => Err(utils::QStashError::InvalidPathSegment(segment.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Callback called after each request, see [`crate::ClientBuilder::on_request`].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type RequestHook =
+    Arc<dyn Fn(&Method, &str, Option<StatusCode>, Duration) + Send + Sync>;
+
+/// Body of a request to the api.
+pub(crate) enum RequestBody {
+    Json(Value),
+    Bytes(Vec<u8>),
+}
+
+impl RequestBody {
+    /// The size of the body in bytes, as it is sent.
+    pub(crate) fn size(&self) -> utils::Result<usize> {
+        match self {
+            RequestBody::Json(body) => Ok(serde_json::to_vec(body)?.len()),
+            RequestBody::Bytes(body) => Ok(body.len()),
+        }
+    }
+
+    /// Gzip the body when it is at least `min_size` bytes long.
+    ///
+    /// Sets `Content-Encoding: gzip` and forwards it to the destination,
+    /// so that QStash delivers the compressed body as is.
+    #[cfg(feature = "compression")]
+    pub(crate) fn compress(
+        self,
+        min_size: Option<usize>,
+        headers: &mut header::HeaderMap,
+    ) -> utils::Result<Self> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let min_size = match min_size {
+            Some(min_size) => min_size,
+            None => return Ok(self),
+        };
+        let bytes = match self {
+            RequestBody::Json(body) => {
+                headers
+                    .entry(header::CONTENT_TYPE)
+                    .or_insert(header::HeaderValue::from_static("application/json"));
+                serde_json::to_vec(&body)?
+            }
+            RequestBody::Bytes(body) => body,
+        };
+        if bytes.len() < min_size {
+            return Ok(RequestBody::Bytes(bytes));
+        }
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&bytes)
+            .map_err(utils::QStashError::Compression)?;
+        let compressed = encoder.finish().map_err(utils::QStashError::Compression)?;
+
+        let gzip = header::HeaderValue::from_static("gzip");
+        headers.insert(header::CONTENT_ENCODING, gzip.clone());
+        headers.insert("Upstash-Forward-Content-Encoding", gzip);
+        Ok(RequestBody::Bytes(compressed))
+    }
+}
+
+impl Client {
+    /// Resolve the url of an api endpoint relative to the base url.
+    pub(crate) fn endpoint(&self, path: &str) -> utils::Result<Url> {
+        Ok(self.api_base_url.join(path)?)
+    }
+
+    /// Resolve the url of an api endpoint from its path segments, e.g. `["topics", name]`.
+    ///
+    /// Each segment is percent-encoded, so ids and names can not change the path or add a query.
+    /// Dot segments are rejected, as they would still be resolved to the parent path.
+    pub(crate) fn resource_endpoint(&self, segments: &[&str]) -> utils::Result<Url> {
+        check_segments(segments.iter().copied())?;
+
+        let mut endpoint = self.api_base_url.clone();
+        endpoint
+            .path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(endpoint)
+    }
+
+    /// Resolve the endpoint of a path ending with a destination url or topic name, e.g. `publish/`.
+    pub(crate) fn destination_endpoint(&self, path: &str, destination: &str) -> utils::Result<Url> {
+        append_destination(self.endpoint(path)?, destination)
+    }
+
+    /// Send a request to the api and deserialize the JSON response.
+    pub(crate) async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        endpoint: Url,
+        headers: Option<header::HeaderMap>,
+        body: Option<RequestBody>,
+    ) -> utils::Result<T> {
+        let response = self
+            .send_request(method, endpoint, headers, body, None)
+            .await?;
+        let body = utils::check_status(response).await?.json().await?;
+        Ok(body)
+    }
+
+    /// Send a publish request and return the JSON response.
+    ///
+    /// The message id is taken from the `Upstash-Message-Id` response header
+    /// when the body does not contain one. The timeout replaces the one of the client,
+    /// and is ignored on wasm32.
+    pub(crate) async fn publish_request(
+        &self,
+        endpoint: Url,
+        headers: header::HeaderMap,
+        body: RequestBody,
+        timeout: Option<Duration>,
+    ) -> utils::Result<Value> {
+        let response = self
+            .send_request(Method::POST, endpoint, Some(headers), Some(body), timeout)
+            .await?;
+        let response = utils::check_status(response).await?;
+
+        let message_id = response
+            .headers()
+            .get("Upstash-Message-Id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut body: Value = response.json().await?;
+        if let (Value::Object(fields), Some(message_id)) = (&mut body, message_id) {
+            fields
+                .entry("messageId")
+                .or_insert(Value::String(message_id));
+        }
+        Ok(body)
+    }
+
+    /// Send a request to an api endpoint that responds without a body.
+    pub(crate) async fn request_no_content(
+        &self,
+        method: Method,
+        endpoint: Url,
+        headers: Option<header::HeaderMap>,
+        body: Option<RequestBody>,
+    ) -> utils::Result<()> {
+        let response = self
+            .send_request(method, endpoint, headers, body, None)
+            .await?;
+        utils::expect_no_content(response).await
+    }
+
+    pub(crate) async fn send_request(
+        &self,
+        method: Method,
+        endpoint: Url,
+        headers: Option<header::HeaderMap>,
+        body: Option<RequestBody>,
+        timeout: Option<Duration>,
+    ) -> utils::Result<Response> {
+        // Only the method and path are recorded, never the headers containing the token.
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "qstash_request",
+            method = %method,
+            path = endpoint.path(),
+            status = tracing::field::Empty,
+            elapsed_ms = tracing::field::Empty,
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let hook = self.on_request.as_ref().map(|hook| {
+            let path = endpoint.path().to_string();
+            (hook, method.clone(), path, std::time::Instant::now())
+        });
+
+        let mut request = self.build_request(method, endpoint, timeout);
+        if let Some(headers) = headers {
+            request = request.headers(headers);
+        }
+        request = match body {
+            Some(RequestBody::Json(body)) => request.json(&body),
+            Some(RequestBody::Bytes(body)) => request.body(body),
+            None => request,
+        };
+
+        #[cfg(feature = "tracing")]
+        let result = self.send_traced(request, span).await;
+        #[cfg(not(feature = "tracing"))]
+        let result = self.send(request).await;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some((hook, method, path, start)) = hook {
+            let status = result.as_ref().ok().map(Response::status);
+            hook(&method, &path, status, start.elapsed());
+        }
+
+        let response = result?;
+        self.record_rate_limit(&response);
+        Ok(response)
+    }
+
+    /// Start a request to the given endpoint with the per-request configuration applied.
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn build_request(
+        &self,
+        method: Method,
+        endpoint: Url,
+        timeout: Option<Duration>,
+    ) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, endpoint)
+            .headers(self.request_headers.clone());
+        #[cfg(not(target_arch = "wasm32"))]
+        let request = match timeout.or(self.request_timeout) {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
+        };
+        request
+    }
+
+    /// Send a request within the span of the request, recording the status and elapsed time.
+    ///
+    /// The elapsed time is not recorded on wasm32, where `Instant` is not available.
+    #[cfg(feature = "tracing")]
+    async fn send_traced(
+        &self,
+        request: RequestBuilder,
+        span: tracing::Span,
+    ) -> utils::Result<Response> {
+        use tracing::Instrument;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = std::time::Instant::now();
+        let result = self.send(request).instrument(span.clone()).await;
+        #[cfg(not(target_arch = "wasm32"))]
+        span.record("elapsed_ms", start.elapsed().as_millis() as u64);
+
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status().as_u16());
+            }
+            Err(e) => span.in_scope(|| tracing::warn!(error = %e, "qstash request failed")),
+        }
+        result
+    }
+
+    /// Send a request, retrying transient failures according to the retry policy.
+    async fn send(&self, request: RequestBuilder) -> utils::Result<Response> {
+        let request = request.build()?;
+
+        if self.retry_policy.max_retries == 0 || !self.retry_policy.is_retryable(&request) {
+            return Ok(self.http.execute(request).await?);
+        }
+
+        let mut attempt = 0;
+        loop {
+            // Requests with a streaming body can not be cloned and are only sent once.
+            let retry = match request.try_clone() {
+                Some(retry) if attempt < self.retry_policy.max_retries => retry,
+                _ => return Ok(self.http.execute(request).await?),
+            };
+
+            let delay = match self.http.execute(retry).await {
+                Ok(response) if self.retry_policy.should_retry(&response) => {
+                    match self.retry_policy.delay(attempt, &response) {
+                        Some(delay) => delay,
+                        // The limit resets too late to wait for it, the caller gets the rate limited response.
+                        None => return Ok(response),
+                    }
+                }
+                Err(e) if retry::is_transient_error(&e) => self.retry_policy.backoff(attempt),
+                result => return Ok(result?),
+            };
+
+            retry::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Remember the rate limit reported by the response, if any.
+    fn record_rate_limit(&self, response: &Response) {
+        if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+            if let Ok(mut last_rate_limit) = self.last_rate_limit.lock() {
+                *last_rate_limit = Some(rate_limit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn publish_endpoint(destination: &str) -> String {
+        let endpoint = Url::parse("https://qstash.upstash.io/v2/publish/").unwrap();
+        append_destination(endpoint, destination)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn topic_names_are_appended_after_the_endpoint() {
+        assert_eq!(
+            publish_endpoint("orders"),
+            "https://qstash.upstash.io/v2/publish/orders"
+        );
+    }
+
+    #[test]
+    fn urls_are_appended_instead_of_replacing_the_base() {
+        assert_eq!(
+            publish_endpoint("https://example.com/hook"),
+            "https://qstash.upstash.io/v2/publish/https://example.com/hook"
+        );
+    }
+
+    #[test]
+    fn query_params_stay_part_of_the_destination() {
+        let endpoint =
+            Url::parse(&publish_endpoint("https://example.com/hook?user=1&tag=a b")).unwrap();
+        assert_eq!(
+            endpoint.as_str(),
+            "https://qstash.upstash.io/v2/publish/https://example.com/hook%3Fuser=1%26tag=a%20b"
+        );
+        assert_eq!(endpoint.query(), None);
+    }
+
+    #[test]
+    fn fragments_and_reserved_characters_are_encoded() {
+        let endpoint =
+            Url::parse(&publish_endpoint("https://example.com/hook?a=1#section")).unwrap();
+        assert_eq!(
+            endpoint.as_str(),
+            "https://qstash.upstash.io/v2/publish/https://example.com/hook%3Fa=1%23section"
+        );
+        assert_eq!(endpoint.fragment(), None);
+
+        assert_eq!(
+            publish_endpoint("https://example.com/100%/{id}"),
+            "https://qstash.upstash.io/v2/publish/https://example.com/100%25/%7Bid%7D"
+        );
+    }
+
+    #[test]
+    fn dot_segments_are_rejected() {
+        let endpoint = Url::parse("https://qstash.upstash.io/v2/publish/").unwrap();
+        for destination in [
+            "..",
+            "../keys",
+            "./keys",
+            "https://../../../keys",
+            "..\\keys",
+        ] {
+            assert!(matches!(
+                append_destination(endpoint.clone(), destination),
+                Err(utils::QStashError::InvalidPathSegment(_))
+            ));
+        }
+        assert!(append_destination(endpoint, "orders..v2").is_ok());
+    }
+}
@@ -0,0 +1,131 @@
+use reqwest::{header, Method, Request, Response, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Configuration for retrying failed requests.
+#[derive(Debug, Clone)]
+pub(crate) struct RetryPolicy {
+    /// Maximum number of retries after the first attempt.
+    pub max_retries: u32,
+    /// Base delay of the exponential backoff.
+    pub backoff: Duration,
+    /// Retry all requests, not only idempotent ones.
+    pub retry_all: bool,
+    /// Status codes of responses that are retried.
+    pub retry_on: Vec<u16>,
+    /// Longest time to wait before a retry.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            backoff: Duration::from_millis(100),
+            retry_all: false,
+            retry_on: vec![429, 502, 503, 504],
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Whether the request may be sent more than once.
+    ///
+    /// GET requests are idempotent, and deduplicated publishes
+    /// are only enqueued once by QStash.
+    pub fn is_retryable(&self, request: &Request) -> bool {
+        self.retry_all
+            || request.method() == Method::GET
+            || request.headers().contains_key("Upstash-Deduplication-Id")
+            || request
+                .headers()
+                .contains_key("Upstash-Content-Based-Deduplication")
+    }
+
+    /// Whether the response indicates a transient failure.
+    pub fn should_retry(&self, response: &Response) -> bool {
+        self.retry_on.contains(&response.status().as_u16())
+    }
+
+    /// The delay before retrying the given attempt, starting at 0, after a transient response.
+    ///
+    /// Rate limited responses are retried once the limit resets, if the response says when.
+    /// Returns `None` if the limit resets after the maximum delay, so the request is not retried.
+    pub fn delay(&self, attempt: u32, response: &Response) -> Option<Duration> {
+        match rate_limit_delay(response) {
+            Some(delay) => (delay <= self.max_delay).then_some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    /// The exponential backoff with jitter before retrying the given attempt,
+    /// capped at the maximum delay.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.backoff.saturating_mul(2u32.saturating_pow(attempt));
+        (backoff + jitter(self.backoff)).min(self.max_delay)
+    }
+}
+
+/// Read the time to wait from the `Retry-After` or `RateLimit-Reset` headers of a 429 response.
+pub(crate) fn rate_limit_delay(response: &Response) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let header_value = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+
+    if let Some(seconds) = header_value(header::RETRY_AFTER.as_str()) {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // RateLimit-Reset is the unix timestamp in seconds when the limit resets.
+    let reset = header_value("RateLimit-Reset")?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(Duration::from_secs(reset.saturating_sub(now)))
+}
+
+/// A pseudo random duration between zero and `max`, spreading out retries of concurrent requests.
+fn jitter(max: Duration) -> Duration {
+    let max_nanos = max.as_nanos() as u64;
+    if max_nanos == 0 {
+        return Duration::ZERO;
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.subsec_nanos() as u64)
+        .unwrap_or(0);
+    Duration::from_nanos(nanos % max_nanos)
+}
+
+/// Whether sending the request failed in a way that is worth retrying.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect()
+}
+
+/// Whether sending the request failed in a way that is worth retrying.
+///
+/// The fetch api does not distinguish connection errors, so only timeouts are retried.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_timeout()
+}
+
+/// Wait before the next retry.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+/// Wait before the next retry.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(delay: Duration) {
+    gloo_timers::future::sleep(delay).await;
+}
@@ -0,0 +1,89 @@
+use crate::{utils, Client};
+use jsonschema::JSONSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+impl Client {
+    /// Validate a message body against a JSON Schema before publishing it.
+    ///
+    /// Fails with [`QStashError::SchemaValidation`](crate::QStashError::SchemaValidation)
+    /// listing all violations, or if the schema itself is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn run(qstash: upstash_qstash::Client, url: &str) -> upstash_qstash::Result<()> {
+    /// let schema = serde_json::json!({
+    ///     "type": "object",
+    ///     "required": ["id"]
+    /// });
+    /// let body = serde_json::json!({ "id": 1 });
+    ///
+    /// qstash.validate_with_schema(&body, &schema)?;
+    /// qstash.publish_json(url, &body, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_with_schema<T: Serialize>(
+        &self,
+        body: &T,
+        schema: &Value,
+    ) -> utils::Result<()> {
+        let schema = JSONSchema::compile(schema)
+            .map_err(|e| utils::QStashError::SchemaValidation(vec![e.to_string()]))?;
+
+        let body = json!(body);
+        let result = schema.validate(&body);
+        if let Err(errors) = result {
+            let violations = errors
+                .map(|e| match e.instance_path.to_string().as_str() {
+                    "" => e.to_string(),
+                    path => format!("{}: {}", path, e),
+                })
+                .collect();
+            return Err(utils::QStashError::SchemaValidation(violations));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["id", "email"],
+            "properties": {
+                "id": { "type": "integer" },
+                "email": { "type": "string" }
+            }
+        })
+    }
+
+    #[test]
+    fn matching_documents_pass() {
+        let client = Client::new("token").unwrap();
+        let body = json!({ "id": 1, "email": "user@example.com" });
+        assert!(client.validate_with_schema(&body, &schema()).is_ok());
+    }
+
+    #[test]
+    fn violations_are_listed() {
+        let client = Client::new("token").unwrap();
+        let body = json!({ "id": "one" });
+        match client.validate_with_schema(&body, &schema()) {
+            Err(utils::QStashError::SchemaValidation(violations)) => {
+                assert_eq!(violations.len(), 2, "{:?}", violations);
+                assert!(violations
+                    .iter()
+                    .any(|violation| violation.starts_with("/id: ")));
+                assert!(violations
+                    .iter()
+                    .any(|violation| violation.contains("email")));
+            }
+            other => panic!("expected schema violations, got {:?}", other),
+        }
+    }
+}
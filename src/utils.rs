@@ -1,22 +1,284 @@
-use reqwest::{header::InvalidHeaderValue, Error as ReqError};
+use crate::retry;
+use reqwest::{
+    header::{InvalidHeaderName, InvalidHeaderValue},
+    Error as ReqError, Response, StatusCode,
+};
 use serde_json::Error as SerdeError;
+use std::time::Duration;
 use thiserror::Error;
-use url::ParseError;
+use url::{ParseError, Url};
 
 /// A type alias for handling errors throughout the library.
 pub type Result<T> = std::result::Result<T, QStashError>;
 
 /// Errors that can occur when using the QStash client.
+///
+/// Errors caused by another error also return it from [`std::error::Error::source`].
 #[derive(Error, Debug)]
 pub enum QStashError {
     #[error("http client failed: {0}")]
     ClientError(#[from] ReqError),
     #[error("invalid header value: {0}")]
     InvalidHeaderValue(#[from] InvalidHeaderValue),
+    #[error("invalid header name: {0}")]
+    InvalidHeaderName(#[from] InvalidHeaderName),
     #[error("parse Url error")]
     UrlError(#[from] ParseError),
     #[error("serialize or deserialize error: {0}")]
     SerdeError(#[from] SerdeError),
+    #[error("unauthorized, check your token: {0}")]
+    Unauthorized(String),
+    #[error("forbidden, the token lacks permission: {0}")]
+    Forbidden(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("rate limited, retry after {retry_after:?}")]
+    RateLimited { retry_after: Duration },
+    /// The api responded with an error status.
+    ///
+    /// `code` is the `error` field of a JSON error body, if the body is one.
+    #[error("api error ({status}): {message}")]
+    Api {
+        status: u16,
+        message: String,
+        code: Option<String>,
+    },
+    #[error("invalid cron expression \"{expression}\": {reason}")]
+    InvalidCron { expression: String, reason: String },
+    #[error("body does not match the schema: {}", .0.join(", "))]
+    SchemaValidation(Vec<String>),
+    #[error("failed to compress the body: {0}")]
+    Compression(#[source] std::io::Error),
+    #[error("failed to start the runtime: {0}")]
+    Runtime(#[source] std::io::Error),
+    #[error("invalid base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[error("invalid signature: {0}")]
+    SignatureError(String),
+    #[error("missing token")]
+    MissingToken,
+    #[error("body of {size} bytes is larger than the limit of {limit} bytes")]
+    BodyTooLarge { size: usize, limit: usize },
+    #[error("{retries} retries are more than the limit of {limit}")]
+    InvalidRetries { retries: u32, limit: u32 },
+    #[error("invalid id or name \"{0}\"")]
+    InvalidPathSegment(String),
+    #[error("path \"{0}\" is not relative to the base url")]
+    InvalidPath(String),
+    #[error("missing destination")]
+    MissingDestination,
+    #[error("missing cron expression")]
+    MissingCron,
+    #[error("missing schedule id")]
+    MissingScheduleId,
     #[error("unknown error")]
     Unknown,
 }
+
+/// Check that a response from an endpoint without a response body was successful.
+///
+/// The body is not parsed, as these endpoints return an empty body on success.
+pub(crate) async fn expect_no_content(response: Response) -> Result<()> {
+    check_status(response).await?;
+    Ok(())
+}
+
+/// Parse the base url of the api server.
+///
+/// A trailing slash is appended when missing, so that joining endpoints
+/// keeps the last path segment of the base url.
+pub(crate) fn parse_base_url(base_url: &str) -> Result<Url> {
+    if base_url.ends_with('/') {
+        Ok(Url::parse(base_url)?)
+    } else {
+        Ok(Url::parse(&format!("{}/", base_url))?)
+    }
+}
+
+/// Check the status of a response from the api.
+///
+/// Responses with a non-success status are turned into an [`QStashError::Api`]
+/// containing the response body, instead of being parsed as a successful result.
+/// The body is kept as raw text whatever its content type, so html error pages
+/// of a proxy in front of the api end up in the error instead of failing to parse.
+/// Authentication failures get their own [`QStashError::Unauthorized`] and
+/// [`QStashError::Forbidden`] errors, missing resources a [`QStashError::NotFound`],
+/// and rate limited responses that say when to retry a [`QStashError::RateLimited`].
+pub(crate) async fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if let Some(retry_after) = retry::rate_limit_delay(&response) {
+        return Err(QStashError::RateLimited { retry_after });
+    }
+
+    let message = response.text().await?;
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        status = status.as_u16(),
+        body = %message,
+        "qstash api responded with an error status"
+    );
+    Err(match status {
+        StatusCode::UNAUTHORIZED => QStashError::Unauthorized(message),
+        StatusCode::FORBIDDEN => QStashError::Forbidden(message),
+        StatusCode::NOT_FOUND => QStashError::NotFound(message),
+        _ => QStashError::Api {
+            status: status.as_u16(),
+            code: error_code(&message),
+            message,
+        },
+    })
+}
+
+/// Read the `error` field of a JSON error body, e.g. `{"error": "invalid destination"}`.
+fn error_code(body: &str) -> Option<String> {
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    body.get("error")?.as_str().map(str::to_string)
+}
+
+/// Validate a cron expression before it is sent to the api.
+///
+/// QStash expects 5 fields, optionally prefixed with a `CRON_TZ=` timezone.
+#[cfg(feature = "cron-validation")]
+pub(crate) fn validate_cron(expression: &str) -> Result<()> {
+    parse_cron(expression)?;
+    Ok(())
+}
+
+/// Parse a cron expression in the format of QStash, ignoring a `CRON_TZ=` prefix.
+#[cfg(feature = "cron-validation")]
+pub(crate) fn parse_cron(expression: &str) -> Result<cron::Schedule> {
+    use std::str::FromStr;
+
+    let invalid = |reason: String| QStashError::InvalidCron {
+        expression: expression.to_string(),
+        reason,
+    };
+
+    let fields = match expression.trim().strip_prefix("CRON_TZ=") {
+        Some(rest) => rest.split_once(' ').map(|(_, fields)| fields).unwrap_or(""),
+        None => expression,
+    };
+
+    let mut fields: Vec<String> = fields.split_whitespace().map(str::to_string).collect();
+    if fields.len() != 5 {
+        return Err(invalid(format!(
+            "expected 5 fields, found {}",
+            fields.len()
+        )));
+    }
+    fields[4] = day_of_week(&fields[4]);
+
+    // The cron crate expects a leading seconds field.
+    cron::Schedule::from_str(&format!("0 {}", fields.join(" "))).map_err(|e| invalid(e.to_string()))
+}
+
+/// Convert a day of week field from the standard numbering, where Sunday is 0 or 7,
+/// to the numbering of the cron crate, where Sunday is 1 and Saturday is 7.
+///
+/// Numeric ranges are expanded into lists, so ranges ending on Sunday don't wrap around.
+/// Names and values that are not valid are kept, for the cron crate to accept or reject.
+#[cfg(feature = "cron-validation")]
+fn day_of_week(field: &str) -> String {
+    let convert = |item: &str| -> Option<String> {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step.parse::<usize>().ok()?)),
+            None => (item, None),
+        };
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (start.parse::<u32>().ok()?, end.parse::<u32>().ok()?),
+            None if range == "*" => return Some(item.to_string()),
+            // `5/2` steps from Friday until the end of the week.
+            None if step.is_some() => (range.parse::<u32>().ok()?, 6),
+            None => (range.parse::<u32>().ok()?, range.parse::<u32>().ok()?),
+        };
+        if start > end || end > 7 || step == Some(0) {
+            return None;
+        }
+
+        let mut days: Vec<u32> = Vec::new();
+        for day in (start..=end).step_by(step.unwrap_or(1)) {
+            let day = day % 7 + 1;
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+        Some(
+            days.iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    };
+
+    field
+        .split(',')
+        .map(|item| convert(item).unwrap_or_else(|| item.to_string()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(not(feature = "cron-validation"))]
+pub(crate) fn validate_cron(_expression: &str) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, feature = "cron-validation"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_cron_expressions_are_accepted() {
+        for expression in [
+            "*/5 * * * *",
+            "0 0 * * 0",
+            "0 0 * * 7",
+            "0 9 * * 1-5",
+            "0 9 * * 5-7",
+            "0 9 * * 0,3,6",
+            "0 9 * * MON-FRI",
+            "CRON_TZ=Europe/Berlin 0 9 * * 1",
+        ] {
+            assert!(
+                validate_cron(expression).is_ok(),
+                "{} was rejected",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_cron_expressions_are_rejected() {
+        for expression in [
+            "* * * *",
+            "* * * * * *",
+            "61 * * * *",
+            "0 0 * * 8",
+            "0 0 * * 5-1",
+            "not a cron",
+        ] {
+            assert!(
+                matches!(
+                    validate_cron(expression),
+                    Err(QStashError::InvalidCron { .. })
+                ),
+                "{} was accepted",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn day_of_week_counts_sunday_as_zero_or_seven() {
+        assert_eq!(day_of_week("0"), "1");
+        assert_eq!(day_of_week("7"), "1");
+        assert_eq!(day_of_week("1-5"), "2,3,4,5,6");
+        assert_eq!(day_of_week("5-7"), "6,7,1");
+        assert_eq!(day_of_week("0,3,6"), "1,4,7");
+        assert_eq!(day_of_week("1-7/2"), "2,4,6,1");
+        assert_eq!(day_of_week("*/2"), "*/2");
+        assert_eq!(day_of_week("MON-FRI"), "MON-FRI");
+    }
+}
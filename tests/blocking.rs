@@ -0,0 +1,71 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use serde_json::json;
+use upstash_qstash::blocking::Client;
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// The mock server runs on its own runtime, as the blocking client can not be used within one.
+fn start_server() -> (tokio::runtime::Runtime, MockServer) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let server = runtime.block_on(MockServer::start());
+    (runtime, server)
+}
+
+#[test]
+fn publish_json_blocks_until_the_message_is_published() {
+    let (runtime, server) = start_server();
+    runtime.block_on(
+        Mock::given(method("POST"))
+            .and(path("/v2/publish/https://example.com/hook"))
+            .and(header("Authorization", "Bearer test-token"))
+            .and(body_json(json!({ "key": "value" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+            .expect(1)
+            .mount(&server),
+    );
+
+    let client = Client::with_base_url(common::TOKEN, &common::base_url(&server)).unwrap();
+    let response = client
+        .publish_json("https://example.com/hook", &json!({ "key": "value" }), None)
+        .unwrap();
+    assert_eq!(response.message_ids(), vec!["msg_1"]);
+}
+
+#[test]
+fn publish_fanout_and_raw_get_are_available() {
+    let (runtime, server) = start_server();
+    runtime.block_on(async {
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v2/quota"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&server)
+            .await;
+    });
+
+    let client = Client::with_base_url(common::TOKEN, &common::base_url(&server)).unwrap();
+    let urls = ["https://a.example.com", "https://b.example.com"];
+    let results = client
+        .publish_fanout(&urls, &json!({}), None, None)
+        .unwrap();
+    assert!(results.iter().all(Result::is_ok));
+
+    let (status, _, body) = client.raw_get("quota").unwrap();
+    assert_eq!(status, 200);
+    assert_eq!(&body[..], b"{}");
+}
+
+#[test]
+fn debug_output_never_contains_the_token() {
+    let client = Client::new("secret-token").unwrap();
+    let debug = format!("{:?}", client);
+    assert!(debug.contains("https://qstash.upstash.io/v2/"));
+    assert!(!debug.contains("secret"));
+}
@@ -0,0 +1,408 @@
+mod common;
+
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use upstash_qstash::{Client, QStashError};
+use wiremock::matchers::{header, header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn with_base_url_sends_requests_to_the_base_url() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    // Without a trailing slash the last path segment of the base url is kept.
+    let base_url = format!("{}/v2", server.uri());
+    let client = Client::with_base_url(common::TOKEN, &base_url).unwrap();
+    client.get_quota().await.unwrap();
+}
+
+#[test]
+fn with_base_url_rejects_invalid_urls() {
+    assert!(Client::with_base_url(common::TOKEN, "not a url").is_err());
+}
+
+#[tokio::test]
+async fn builder_timeout_fails_slow_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "used": 1, "max": 500 }))
+                .set_delay(Duration::from_millis(500)),
+        )
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .timeout(Duration::from_millis(1))
+        .build()
+        .unwrap();
+    match client.get_quota().await {
+        Err(QStashError::ClientError(e)) => assert!(e.is_timeout()),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn builder_adds_the_token_to_requests_of_a_custom_http_client() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .http_client(reqwest::Client::new())
+        .build()
+        .unwrap();
+    client.get_quota().await.unwrap();
+}
+
+#[test]
+fn builder_requires_a_token() {
+    assert!(matches!(
+        Client::builder().build(),
+        Err(QStashError::MissingToken)
+    ));
+}
+
+#[tokio::test]
+async fn endpoints_share_authentication_and_error_handling() {
+    let (server, client) = common::setup().await;
+    for endpoint in ["/v2/quota", "/v2/topics"] {
+        Mock::given(method("GET"))
+            .and(path(endpoint))
+            .and(header("Authorization", "Bearer test-token"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("internal error"))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let quota = client.get_quota().await.unwrap_err();
+    let topics = client.list_topics().await.unwrap_err();
+    for error in [quota, topics] {
+        assert!(
+            matches!(&error, QStashError::Api { status: 500, message, .. } if message == "internal error"),
+            "{:?}",
+            error
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_underlying_http_client_sends_the_token() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/unmodeled"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client
+        .http()
+        .get(format!("{}unmodeled", common::base_url(&server)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+}
+
+#[tokio::test]
+async fn requests_identify_the_client_with_a_user_agent() {
+    let server = MockServer::start().await;
+    let default_user_agent = concat!("upstash-qstash-rust/", env!("CARGO_PKG_VERSION"));
+    for user_agent in [default_user_agent, "my-app/1.0"] {
+        Mock::given(method("GET"))
+            .and(path("/v2/quota"))
+            .and(header("User-Agent", user_agent))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let default_client = common::setup_with_server(&server);
+    default_client.get_quota().await.unwrap();
+
+    let custom_client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .user_agent("my-app/1.0")
+        .build()
+        .unwrap();
+    custom_client.get_quota().await.unwrap();
+}
+
+#[tokio::test]
+async fn clients_with_custom_pool_settings_send_requests() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .pool_max_idle_per_host(1)
+        .pool_idle_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    client.get_quota().await.unwrap();
+    client.get_quota().await.unwrap();
+}
+
+#[test]
+fn debug_output_never_contains_the_token() {
+    let token = "secret-token-123";
+    let client = Client::builder()
+        .token(token)
+        .base_url("https://qstash.example.com/v2/")
+        .build()
+        .unwrap();
+
+    let debug = format!("{:?}", client);
+    assert!(debug.contains("https://qstash.example.com/v2/"));
+    assert!(!debug.contains("secret"));
+    assert!(!format!("{:?}", Client::builder().token(token)).contains("secret"));
+}
+
+#[tokio::test]
+async fn check_destination_is_true_for_reachable_urls() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("HEAD"))
+        .and(path("/hook"))
+        .and(header_exists("Authorization"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/hook", server.uri());
+    assert!(client.check_destination(&url).await.unwrap());
+}
+
+#[tokio::test]
+async fn check_destination_is_false_for_unreachable_urls() {
+    let client = Client::new(common::TOKEN).unwrap();
+    // Bind and release a port, so nothing is listening on it.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    let url = format!("http://127.0.0.1:{}/hook", port);
+    assert!(!client.check_destination(&url).await.unwrap());
+}
+
+#[tokio::test]
+async fn check_destination_is_false_for_urls_that_never_respond() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let started = std::time::Instant::now();
+    let url = format!("{}/hook", server.uri());
+    assert!(!client.check_destination(&url).await.unwrap());
+    assert!(started.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn raw_get_returns_the_response_as_is() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/messages/msg_1"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(
+            ResponseTemplate::new(404)
+                .insert_header("X-Request-Id", "req_1")
+                .set_body_string("message not found"),
+        )
+        .mount(&server)
+        .await;
+
+    let (status, headers, body) = client.raw_get("messages/msg_1").await.unwrap();
+    assert_eq!(status, 404);
+    assert_eq!(headers["X-Request-Id"], "req_1");
+    assert_eq!(&body[..], b"message not found");
+}
+
+#[tokio::test]
+async fn raw_get_rejects_paths_outside_of_the_base_url() {
+    let (server, client) = common::setup().await;
+    let other = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&other)
+        .await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let host = other.uri().trim_start_matches("http://").to_string();
+    for path in [
+        format!("{}/x", other.uri()),
+        format!("//{}/x", host),
+        "/keys".to_string(),
+        "../keys".to_string(),
+    ] {
+        match client.raw_get(&path).await {
+            Err(QStashError::InvalidPath(rejected)) => assert_eq!(rejected, path),
+            other => panic!(
+                "expected an invalid path error for {}, got {:?}",
+                path, other
+            ),
+        }
+    }
+}
+
+fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+#[test]
+fn the_client_can_be_shared_between_threads() {
+    assert_send_sync::<Client>();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn cloned_clients_publish_concurrently() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "messageId": "msg_1" }))
+                .insert_header("RateLimit-Limit", "100")
+                .insert_header("RateLimit-Remaining", "99")
+                .insert_header("RateLimit-Reset", "1700000000"),
+        )
+        .expect(20)
+        .mount(&server)
+        .await;
+
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                client
+                    .publish_json("orders", &json!({ "task": i }), None)
+                    .await
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    // Clones share the last rate limit.
+    assert_eq!(client.last_rate_limit().unwrap().remaining, 99);
+}
+
+#[tokio::test]
+async fn on_request_reports_each_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "used": 1, "max": 500 }))
+                .set_delay(Duration::from_millis(50)),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages/msg_1"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = calls.clone();
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .on_request(move |method, path, status, elapsed| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((method.clone(), path.to_string(), status, elapsed));
+        })
+        .build()
+        .unwrap();
+
+    client.get_quota().await.unwrap();
+    assert!(client.cancel_message("msg_1").await.is_err());
+
+    let calls = calls.lock().unwrap();
+    assert_eq!(calls.len(), 2);
+    let (method, path, status, elapsed) = &calls[0];
+    assert_eq!(method, reqwest::Method::GET);
+    assert_eq!(path, "/v2/quota");
+    assert_eq!(*status, Some(reqwest::StatusCode::OK));
+    assert!(*elapsed >= Duration::from_millis(50));
+    assert!(*elapsed < Duration::from_secs(5));
+
+    let (method, path, status, _) = &calls[1];
+    assert_eq!(method, reqwest::Method::DELETE);
+    assert_eq!(path, "/v2/messages/msg_1");
+    assert_eq!(*status, Some(reqwest::StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn on_request_reports_no_status_without_a_response() {
+    let port = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = statuses.clone();
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&format!("http://127.0.0.1:{port}/v2/"))
+        .on_request(move |_, _, status, _| recorded.lock().unwrap().push(status))
+        .build()
+        .unwrap();
+
+    assert!(client.get_quota().await.is_err());
+    assert_eq!(*statuses.lock().unwrap(), vec![None]);
+}
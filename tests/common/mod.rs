@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use upstash_qstash::Client;
+use wiremock::MockServer;
+
+pub const TOKEN: &str = "test-token";
+
+/// Start a mock server and a client sending requests to it.
+pub async fn setup() -> (MockServer, Client) {
+    let server = MockServer::start().await;
+    let client = Client::with_base_url(TOKEN, &base_url(&server)).unwrap();
+    (server, client)
+}
+
+/// The base url of the api on the mock server.
+pub fn base_url(server: &MockServer) -> String {
+    format!("{}/v2/", server.uri())
+}
+
+/// A client sending requests to an already started mock server.
+pub fn setup_with_server(server: &MockServer) -> Client {
+    Client::with_base_url(TOKEN, &base_url(server)).unwrap()
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use serde_json::json;
+use upstash_qstash::QStashError;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn dlq_list_deserializes_a_page() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("cursor", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "cursor": "page-3",
+            "messages": [{
+                "dlqId": "dlq_1",
+                "messageId": "msg_1",
+                "url": "https://example.com/hook",
+                "responseStatus": 500,
+                "responseBody": "internal error"
+            }]
+        })))
+        .mount(&server)
+        .await;
+
+    let page = client.dlq_list(Some("page-2")).await.unwrap();
+    assert_eq!(page.cursor.as_deref(), Some("page-3"));
+    let message = &page.messages[0];
+    assert_eq!(message.dlq_id.as_deref(), Some("dlq_1"));
+    assert_eq!(message.message_id, "msg_1");
+    assert_eq!(message.response_status, Some(500));
+    assert_eq!(message.response_body.as_deref(), Some("internal error"));
+}
+
+#[tokio::test]
+async fn dlq_list_returns_the_error_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(path("/v2/dlq"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("database unavailable"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.dlq_list(None).await,
+        Err(QStashError::Api { status: 500, message, .. }) if message == "database unavailable"
+    ));
+}
+
+#[tokio::test]
+async fn dlq_delete_accepts_an_empty_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/dlq_1"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.dlq_delete("dlq_1").await.unwrap();
+}
+
+#[tokio::test]
+async fn dlq_delete_of_a_missing_message_fails() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/dlq/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("message not found"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.dlq_delete("missing").await,
+        Err(QStashError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn dlq_get_returns_the_message() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq/dlq_1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "dlqId": "dlq_1", "messageId": "msg_1" })),
+        )
+        .mount(&server)
+        .await;
+
+    let message = client.dlq_get("dlq_1").await.unwrap();
+    assert_eq!(message.message_id, "msg_1");
+}
+
+#[tokio::test]
+async fn dlq_list_all_returns_the_messages_of_all_pages() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "cursor": "page-2",
+            "messages": [{ "messageId": "msg_1" }, { "messageId": "msg_2" }]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/dlq"))
+        .and(query_param("cursor", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "cursor": "",
+            "messages": [{ "messageId": "msg_3" }]
+        })))
+        .mount(&server)
+        .await;
+
+    let messages = client.dlq_list_all(100).await.unwrap();
+    let ids: Vec<_> = messages
+        .iter()
+        .map(|message| message.message_id.as_str())
+        .collect();
+    assert_eq!(ids, ["msg_1", "msg_2", "msg_3"]);
+}
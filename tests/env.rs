@@ -0,0 +1,24 @@
+//! Kept in its own test binary with a single test, as it changes the environment of the process.
+
+use upstash_qstash::{Client, QStashError};
+
+#[test]
+fn from_env_reads_the_token_and_base_url() {
+    std::env::remove_var("QSTASH_TOKEN");
+    std::env::remove_var("QSTASH_URL");
+    assert!(matches!(Client::from_env(), Err(QStashError::MissingToken)));
+
+    std::env::set_var("QSTASH_TOKEN", "env-token");
+    let client = Client::from_env().unwrap();
+    assert!(format!("{:?}", client).contains("https://qstash.upstash.io/v2/"));
+
+    std::env::set_var("QSTASH_URL", "http://localhost:8080/v2");
+    let client = Client::from_env().unwrap();
+    assert!(format!("{:?}", client).contains("http://localhost:8080/v2/"));
+
+    std::env::set_var("QSTASH_URL", "not a url");
+    assert!(matches!(Client::from_env(), Err(QStashError::UrlError(_))));
+
+    std::env::remove_var("QSTASH_TOKEN");
+    std::env::remove_var("QSTASH_URL");
+}
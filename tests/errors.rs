@@ -0,0 +1,175 @@
+mod common;
+
+use std::time::Duration;
+use upstash_qstash::QStashError;
+use wiremock::matchers::path;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn respond_with(server: &MockServer, response: ResponseTemplate) {
+    Mock::given(path("/v2/quota"))
+        .respond_with(response)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn unauthorized_responses_keep_the_message() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(401).set_body_string("invalid token"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Unauthorized(message)) => assert_eq!(message, "invalid token"),
+        other => panic!("expected an unauthorized error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_responses_keep_the_status_and_message() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(429).set_body_string("too many requests"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Api {
+            status, message, ..
+        }) => {
+            assert_eq!(status, 429);
+            assert_eq!(message, "too many requests");
+        }
+        other => panic!("expected an api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn server_errors_keep_the_status_and_message() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(500).set_body_string("internal error"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Api {
+            status, message, ..
+        }) => {
+            assert_eq!(status, 500);
+            assert_eq!(message, "internal error");
+        }
+        other => panic!("expected an api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn forbidden_responses_keep_the_message() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(403).set_body_string("token lacks permission"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Forbidden(message)) => assert_eq!(message, "token lacks permission"),
+        other => panic!("expected a forbidden error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn rate_limited_responses_say_when_to_retry() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(429).insert_header("Retry-After", "2"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::RateLimited { retry_after }) => {
+            assert_eq!(retry_after, Duration::from_secs(2))
+        }
+        other => panic!("expected a rate limited error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn html_error_pages_become_api_errors() {
+    let (server, client) = common::setup().await;
+    let page = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+    respond_with(
+        &server,
+        ResponseTemplate::new(502).set_body_raw(page, "text/html"),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Api {
+            status,
+            message,
+            code,
+        }) => {
+            assert_eq!(status, 502);
+            assert_eq!(message, page);
+            assert_eq!(code, None);
+        }
+        other => panic!("expected an api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn json_error_bodies_expose_their_code() {
+    let (server, client) = common::setup().await;
+    respond_with(
+        &server,
+        ResponseTemplate::new(400)
+            .set_body_json(serde_json::json!({ "error": "invalid destination" })),
+    )
+    .await;
+
+    match client.get_quota().await {
+        Err(QStashError::Api { status, code, .. }) => {
+            assert_eq!(status, 400);
+            assert_eq!(code.as_deref(), Some("invalid destination"));
+        }
+        other => panic!("expected an api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn client_errors_expose_the_reqwest_error_as_source() {
+    use std::error::Error;
+
+    // Bind and release a port, so nothing is listening on it.
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let base_url = format!("http://127.0.0.1:{}/v2/", port);
+    let client = upstash_qstash::Client::with_base_url(common::TOKEN, &base_url).unwrap();
+
+    let error = client.get_quota().await.unwrap_err();
+    assert!(matches!(error, QStashError::ClientError(_)));
+    assert!(error.to_string().starts_with("http client failed: "));
+
+    let source = error.source().expect("the reqwest error");
+    let reqwest_error = source.downcast_ref::<reqwest::Error>().unwrap();
+    assert!(reqwest_error.is_connect());
+
+    let mut chain = vec![error.to_string()];
+    let mut next = error.source();
+    while let Some(source) = next {
+        chain.push(source.to_string());
+        next = source.source();
+    }
+    assert!(chain.len() >= 2, "{:?}", chain);
+    // The message includes the cause, for users printing only the top level error.
+    assert!(chain[0].contains(&chain[1]));
+}
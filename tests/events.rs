@@ -0,0 +1,219 @@
+mod common;
+
+use serde_json::json;
+use upstash_qstash::EventFilter;
+use wiremock::matchers::{header, method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mount_two_pages(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("state", "ERROR"))
+        .and(query_param_is_missing("cursor"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "cursor": "page-2",
+            "events": [{ "time": 3, "messageId": "msg_3" }, { "time": 2, "messageId": "msg_2" }]
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("state", "ERROR"))
+        .and(query_param("cursor", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "events": [{ "time": 1, "messageId": "msg_1" }]
+        })))
+        .mount(server)
+        .await;
+}
+
+fn errors() -> EventFilter {
+    EventFilter {
+        state: Some("ERROR".to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn list_events_all_follows_the_cursor_with_the_same_filter() {
+    let (server, client) = common::setup().await;
+    mount_two_pages(&server).await;
+
+    let events = client.list_events_all(errors(), 100).await.unwrap();
+    let ids: Vec<_> = events
+        .iter()
+        .map(|event| event.message_id.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids, ["msg_3", "msg_2", "msg_1"]);
+}
+
+#[tokio::test]
+async fn list_events_all_stops_at_the_limit() {
+    let (server, client) = common::setup().await;
+    mount_two_pages(&server).await;
+
+    let events = client.list_events_all(errors(), 2).await.unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn schedule_messages_filters_events_by_schedule() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("scheduleId", "scd_1"))
+        .and(query_param("cursor", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "cursor": "page-3",
+            "events": [
+                { "time": 2, "messageId": "msg_2", "state": "DELIVERED" },
+                { "time": 1, "messageId": "msg_1", "state": "CREATED" }
+            ]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let page = client
+        .schedule_messages("scd_1", Some("page-2"))
+        .await
+        .unwrap();
+    assert_eq!(page.cursor.as_deref(), Some("page-3"));
+    let ids: Vec<_> = page
+        .events
+        .iter()
+        .map(|event| event.message_id.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids, ["msg_2", "msg_1"]);
+}
+
+#[tokio::test]
+async fn gzip_encoded_responses_are_decompressed() {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let (server, client) = common::setup().await;
+    let page = json!({
+        "events": (0..100)
+            .map(|i| json!({ "time": i, "messageId": format!("msg_{}", i), "state": "DELIVERED" }))
+            .collect::<Vec<_>>()
+    });
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&serde_json::to_vec(&page).unwrap())
+        .unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(header("Accept-Encoding", "gzip"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("Content-Encoding", "gzip")
+                .set_body_raw(compressed, "application/json"),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let events = client.list_events(EventFilter::default()).await.unwrap();
+    assert_eq!(events.events.len(), 100);
+    assert_eq!(events.events[99].message_id.as_deref(), Some("msg_99"));
+}
+
+#[tokio::test]
+async fn get_message_response_uses_the_latest_recorded_response() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("messageId", "msg_1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "events": [
+                { "time": 3, "state": "DELIVERED" },
+                { "time": 2, "state": "ERROR", "responseStatus": 500, "responseBody": "retry me" },
+                { "time": 1, "state": "ERROR", "responseStatus": 502, "responseBody": "older" }
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .and(query_param("messageId", "msg_new"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "events": [{ "time": 1, "state": "CREATED" }]
+        })))
+        .mount(&server)
+        .await;
+
+    let response = client.get_message_response("msg_1").await.unwrap();
+    assert_eq!(response.status, 500);
+    assert_eq!(response.body, "retry me");
+
+    let missing = client.get_message_response("msg_new").await;
+    assert!(matches!(
+        missing,
+        Err(upstash_qstash::QStashError::NotFound(_))
+    ));
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn events_stream_follows_the_cursor_until_it_is_exhausted() {
+    use futures::TryStreamExt;
+
+    let (server, client) = common::setup().await;
+    let pages = [
+        (
+            None,
+            json!({ "cursor": "page-2", "events": [{ "time": 3, "messageId": "msg_3" }, { "time": 2, "messageId": "msg_2" }] }),
+        ),
+        (
+            Some("page-2"),
+            json!({ "cursor": "page-3", "events": [{ "time": 1, "messageId": "msg_1" }] }),
+        ),
+        (Some("page-3"), json!({ "cursor": "", "events": [] })),
+    ];
+    for (cursor, page) in pages {
+        let mock = Mock::given(method("GET"))
+            .and(path("/v2/events"))
+            .and(query_param("messageId", "msg_1"));
+        let mock = match cursor {
+            Some(cursor) => mock.and(query_param("cursor", cursor)),
+            None => mock.and(query_param_is_missing("cursor")),
+        };
+        mock.respond_with(ResponseTemplate::new(200).set_body_json(page))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let filter = EventFilter {
+        message_id: Some("msg_1".to_string()),
+        ..Default::default()
+    };
+    let events: Vec<_> = client.events_stream(filter).try_collect().await.unwrap();
+    let ids: Vec<_> = events
+        .iter()
+        .map(|event| event.message_id.as_deref().unwrap())
+        .collect();
+    assert_eq!(ids, ["msg_3", "msg_2", "msg_1"]);
+}
+
+#[cfg(feature = "stream")]
+#[tokio::test]
+async fn events_stream_ends_after_an_error() {
+    use futures::StreamExt;
+
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/events"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("internal error"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let items: Vec<_> = client.events_stream(EventFilter::default()).collect().await;
+    assert_eq!(items.len(), 1);
+    assert!(items[0].is_err());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn get_signing_keys_returns_both_keys() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/keys"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "current": "sig_current", "next": "sig_next" })),
+        )
+        .mount(&server)
+        .await;
+
+    let keys = client.get_signing_keys().await.unwrap();
+    assert_eq!(keys.current, "sig_current");
+    assert_eq!(keys.next, "sig_next");
+}
+
+#[tokio::test]
+async fn rotate_signing_keys_posts_and_returns_the_new_keys() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/keys/rotate"))
+        .and(header("Authorization", "Bearer test-token"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "current": "sig_next", "next": "sig_new" })),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let keys = client.rotate_signing_keys().await.unwrap();
+    assert_eq!(keys.current, "sig_next");
+    assert_eq!(keys.next, "sig_new");
+}
@@ -0,0 +1,176 @@
+mod common;
+
+use serde_json::json;
+use upstash_qstash::QStashError;
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn cancel_messages_sends_all_ids() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .and(body_json(
+            json!({ "messageIds": ["msg_1", "msg_2", "msg_3"] }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "cancelled": 3 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .cancel_messages(&["msg_1", "msg_2", "msg_3"])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn cancel_messages_without_ids_sends_nothing() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    client.cancel_messages(&[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn cancel_message_accepts_an_empty_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages/msg_1"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.cancel_message("msg_1").await.unwrap();
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Order {
+    id: u32,
+    item: String,
+}
+
+#[tokio::test]
+async fn get_message_body_round_trips_a_typed_body() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let (server, client) = common::setup().await;
+    let order = Order {
+        id: 7,
+        item: "book".to_string(),
+    };
+    let body = serde_json::to_string(&order).unwrap();
+    for (message_id, body) in [
+        ("msg_plain", body.clone()),
+        ("msg_base64", STANDARD.encode(&body)),
+    ] {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/messages/{}", message_id)))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "messageId": message_id, "body": body })),
+            )
+            .mount(&server)
+            .await;
+    }
+
+    let plain: Order = client.get_message_body("msg_plain").await.unwrap();
+    assert_eq!(plain, order);
+    let decoded: Order = client.get_message_body("msg_base64").await.unwrap();
+    assert_eq!(decoded, order);
+}
+
+#[tokio::test]
+async fn cancel_by_schedule_returns_the_number_of_canceled_messages() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages"))
+        .and(body_json(json!({ "scheduleId": "scd_1" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "cancelled": 4 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.cancel_by_schedule("scd_1").await.unwrap(), 4);
+
+    let requests = server.received_requests().await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body, json!({ "scheduleId": "scd_1" }));
+}
+
+#[tokio::test]
+async fn cancel_by_schedule_sends_nothing_without_a_schedule_id() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "cancelled": 100 })))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    for schedule_id in ["", "  "] {
+        assert!(matches!(
+            client.cancel_by_schedule(schedule_id).await,
+            Err(QStashError::MissingScheduleId)
+        ));
+    }
+}
+
+#[tokio::test]
+async fn get_messages_returns_a_result_per_id_in_order() {
+    let (server, client) = common::setup().await;
+    for message_id in ["msg_1", "msg_3"] {
+        Mock::given(method("GET"))
+            .and(path(format!("/v2/messages/{}", message_id)))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!({ "messageId": message_id })),
+            )
+            .mount(&server)
+            .await;
+    }
+    Mock::given(method("GET"))
+        .and(path("/v2/messages/msg_2"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("message not found"))
+        .mount(&server)
+        .await;
+
+    let messages = client
+        .get_messages(&["msg_1", "msg_2", "msg_3"])
+        .await
+        .unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(
+        messages[0].as_ref().unwrap().message_id.as_deref(),
+        Some("msg_1")
+    );
+    assert!(matches!(messages[1], Err(QStashError::NotFound(_))));
+    assert_eq!(
+        messages[2].as_ref().unwrap().message_id.as_deref(),
+        Some("msg_3")
+    );
+}
+
+#[tokio::test]
+async fn cancel_message_distinguishes_missing_messages() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages/msg_1"))
+        .respond_with(ResponseTemplate::new(202))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/messages/msg_gone"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("message not found"))
+        .mount(&server)
+        .await;
+
+    client.cancel_message("msg_1").await.unwrap();
+    match client.cancel_message("msg_gone").await {
+        Err(QStashError::NotFound(message)) => assert_eq!(message, "message not found"),
+        other => panic!("expected a not found error, got {:?}", other),
+    }
+}
@@ -0,0 +1,257 @@
+//! A hand-written mock of the [`QStash`] trait, standing in for the [`Client`](upstash_qstash::Client)
+//! in the tests of code that publishes messages.
+
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use upstash_qstash::{
+    BatchItem, DeliveryResponse, Destination, DlqList, DlqMessage, Event, EventFilter, EventList,
+    Message, MessageSettings, PublishResponse, PublishResponses, QStash, Queue, Quota, Result,
+    Schedule, ScheduleResponse, SigningKeys, Topic,
+};
+
+/// Application code, depending on the trait instead of the client.
+async fn notify_signup(qstash: &impl QStash, email: &str) -> Result<String> {
+    let response = qstash
+        .publish_json(
+            "https://example.com/api/welcome",
+            &json!({ "email": email }),
+            None,
+        )
+        .await?;
+    Ok(response.message_ids().remove(0))
+}
+
+/// Records published messages instead of sending them.
+#[derive(Default)]
+struct MockQStash {
+    published: Mutex<Vec<(String, Value)>>,
+}
+
+impl QStash for MockQStash {
+    async fn raw_get(&self, _path: &str) -> Result<(StatusCode, HeaderMap, Bytes)> {
+        unimplemented!()
+    }
+
+    async fn get_quota(&self) -> Result<Quota> {
+        unimplemented!()
+    }
+
+    async fn get_message(&self, _message_id: &str) -> Result<Message> {
+        unimplemented!()
+    }
+
+    async fn get_messages(&self, _message_ids: &[&str]) -> Result<Vec<Result<Message>>> {
+        unimplemented!()
+    }
+
+    async fn get_message_raw(&self, _message_id: &str) -> Result<Value> {
+        unimplemented!()
+    }
+
+    async fn get_message_body<T: DeserializeOwned + Send>(&self, _message_id: &str) -> Result<T> {
+        unimplemented!()
+    }
+
+    async fn publish_json<T: Serialize + Sync>(
+        &self,
+        url_or_topic: &str,
+        body: &T,
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<PublishResponses> {
+        let mut published = self.published.lock().unwrap();
+        published.push((url_or_topic.to_string(), serde_json::to_value(body)?));
+        Ok(PublishResponses::Single(PublishResponse {
+            message_id: format!("msg_{}", published.len()),
+            url: None,
+            deduplicated: false,
+        }))
+    }
+
+    async fn publish<T: Serialize + Sync>(
+        &self,
+        _destination: Destination,
+        _body: &T,
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<PublishResponses> {
+        unimplemented!()
+    }
+
+    async fn publish_fanout<T: Serialize + Sync>(
+        &self,
+        _urls: &[&str],
+        _body: &T,
+        _message_settings: Option<MessageSettings>,
+        _concurrency: Option<usize>,
+    ) -> Result<Vec<Result<PublishResponse>>> {
+        unimplemented!()
+    }
+
+    async fn publish_bytes(
+        &self,
+        _url_or_topic: &str,
+        _body: Vec<u8>,
+        _content_type: &str,
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<PublishResponses> {
+        unimplemented!()
+    }
+
+    async fn publish_form(
+        &self,
+        _url_or_topic: &str,
+        _form: &[(&str, &str)],
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<PublishResponses> {
+        unimplemented!()
+    }
+
+    async fn publish_batch(&self, _items: Vec<BatchItem>) -> Result<Vec<PublishResponse>> {
+        unimplemented!()
+    }
+
+    async fn check_destination(&self, _url: &str) -> Result<bool> {
+        unimplemented!()
+    }
+
+    async fn create_schedule(
+        &self,
+        _destination: &str,
+        _cron: &str,
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<ScheduleResponse> {
+        unimplemented!()
+    }
+
+    async fn list_schedules(&self) -> Result<Vec<Schedule>> {
+        unimplemented!()
+    }
+
+    async fn get_schedule(&self, _schedule_id: &str) -> Result<Schedule> {
+        unimplemented!()
+    }
+
+    async fn delete_schedule(&self, _schedule_id: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn pause_schedule(&self, _schedule_id: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn resume_schedule(&self, _schedule_id: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn list_topics(&self) -> Result<Vec<Topic>> {
+        unimplemented!()
+    }
+
+    async fn delete_topic(&self, _name: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn add_endpoint(&self, _topic: &str, _name: &str, _url: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn remove_endpoint(&self, _topic: &str, _endpoint_name: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn create_queue(&self, _name: &str, _parallelism: u32) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn list_queues(&self) -> Result<Vec<Queue>> {
+        unimplemented!()
+    }
+
+    async fn delete_queue(&self, _name: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn publish_to_queue<T: Serialize + Sync>(
+        &self,
+        _queue: &str,
+        _url_or_topic: &str,
+        _body: &T,
+        _message_settings: Option<MessageSettings>,
+    ) -> Result<PublishResponses> {
+        unimplemented!()
+    }
+
+    async fn cancel_message(&self, _message_id: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn cancel_messages(&self, _message_ids: &[&str]) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn cancel_by_schedule(&self, _schedule_id: &str) -> Result<u64> {
+        unimplemented!()
+    }
+
+    async fn dlq_list(&self, _cursor: Option<&str>) -> Result<DlqList> {
+        unimplemented!()
+    }
+
+    async fn dlq_list_all(&self, _limit: usize) -> Result<Vec<DlqMessage>> {
+        unimplemented!()
+    }
+
+    async fn dlq_get(&self, _dlq_message_id: &str) -> Result<DlqMessage> {
+        unimplemented!()
+    }
+
+    async fn dlq_delete(&self, _dlq_message_id: &str) -> Result<()> {
+        unimplemented!()
+    }
+
+    async fn list_events(&self, _filter: EventFilter) -> Result<EventList> {
+        unimplemented!()
+    }
+
+    async fn get_message_response(&self, _message_id: &str) -> Result<DeliveryResponse> {
+        unimplemented!()
+    }
+
+    async fn list_events_all(&self, _filter: EventFilter, _limit: usize) -> Result<Vec<Event>> {
+        unimplemented!()
+    }
+
+    async fn schedule_messages(
+        &self,
+        _schedule_id: &str,
+        _cursor: Option<&str>,
+    ) -> Result<EventList> {
+        unimplemented!()
+    }
+
+    async fn get_signing_keys(&self) -> Result<SigningKeys> {
+        unimplemented!()
+    }
+
+    async fn rotate_signing_keys(&self) -> Result<SigningKeys> {
+        unimplemented!()
+    }
+}
+
+#[tokio::test]
+async fn application_code_can_be_tested_with_a_mock() {
+    let qstash = MockQStash::default();
+
+    let message_id = notify_signup(&qstash, "user@example.com").await.unwrap();
+
+    assert_eq!(message_id, "msg_1");
+    assert_eq!(
+        *qstash.published.lock().unwrap(),
+        [(
+            "https://example.com/api/welcome".to_string(),
+            json!({ "email": "user@example.com" })
+        )]
+    );
+}
@@ -0,0 +1,546 @@
+mod common;
+
+use serde_json::json;
+use std::time::Duration;
+use upstash_qstash::{
+    BatchItem, Client, Destination, MessageSettings, PublishRequest, PublishResponses, QStashError,
+};
+use wiremock::matchers::{body_bytes, body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn publish_json_to_a_url_returns_a_single_response() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/hook"))
+        .and(body_json(json!({ "key": "value" })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .mount(&server)
+        .await;
+
+    let response = client
+        .publish_json("https://example.com/hook", &json!({ "key": "value" }), None)
+        .await
+        .unwrap();
+    assert!(matches!(response, PublishResponses::Single(_)));
+    assert_eq!(response.message_ids(), vec!["msg_1"]);
+}
+
+#[tokio::test]
+async fn publish_json_to_a_topic_returns_a_response_per_endpoint() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "messageId": "msg_1", "url": "https://a.example.com" },
+            { "messageId": "msg_2", "url": "https://b.example.com" }
+        ])))
+        .mount(&server)
+        .await;
+
+    let response = client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .unwrap();
+    assert!(matches!(response, PublishResponses::List(_)));
+    assert_eq!(response.message_ids(), vec!["msg_1", "msg_2"]);
+}
+
+#[tokio::test]
+async fn publish_json_raw_returns_the_response_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .mount(&server)
+        .await;
+
+    let response = client
+        .publish_json_raw("orders", &json!({}), None)
+        .await
+        .unwrap();
+    assert_eq!(response, json!({ "messageId": "msg_1" }));
+}
+
+#[tokio::test]
+async fn publish_bytes_sends_the_body_with_its_content_type_and_settings() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/hook"))
+        .and(body_bytes(b"plain text".to_vec()))
+        .and(header("Content-Type", "text/plain"))
+        .and(header("Authorization", "Bearer test-token"))
+        .and(header("Upstash-Retries", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .publish_bytes(
+            "https://example.com/hook",
+            b"plain text".to_vec(),
+            "text/plain",
+            MessageSettings::new().retries(2),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn publish_batch_nests_the_settings_of_each_item_as_headers() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/batch"))
+        .and(body_json(json!([
+            {
+                "destination": "https://example.com/a",
+                "headers": { "content-type": "application/json", "upstash-retries": "1" },
+                "body": "{\"id\":1}"
+            },
+            {
+                "destination": "orders",
+                "headers": { "content-type": "text/plain", "upstash-delay": "10s" },
+                "body": "two"
+            }
+        ])))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "messageId": "msg_1" },
+            { "messageId": "msg_2" }
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let responses = client
+        .publish_batch(vec![
+            BatchItem::new("https://example.com/a", r#"{"id":1}"#, "application/json")
+                .settings(MessageSettings::new().retries(1)),
+            BatchItem::new("orders", "two", "text/plain")
+                .settings(MessageSettings::new().delay("10s")),
+        ])
+        .await
+        .unwrap();
+    let ids: Vec<_> = responses
+        .iter()
+        .map(|response| response.message_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["msg_1", "msg_2"]);
+}
+
+#[tokio::test]
+async fn publish_records_the_rate_limit_headers() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "messageId": "msg_1" }))
+                .insert_header("RateLimit-Limit", "100")
+                .insert_header("RateLimit-Remaining", "42")
+                .insert_header("RateLimit-Reset", "1700000000"),
+        )
+        .mount(&server)
+        .await;
+
+    assert_eq!(client.last_rate_limit(), None);
+    client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .unwrap();
+
+    let rate_limit = client.last_rate_limit().unwrap();
+    assert_eq!(rate_limit.limit, 100);
+    assert_eq!(rate_limit.remaining, 42);
+    assert_eq!(rate_limit.reset, 1_700_000_000);
+}
+
+#[tokio::test]
+async fn publish_with_an_invalid_callback_url_fails_without_a_request() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let settings = MessageSettings::new().callback_url("https://example.com/\ncallback");
+    let result = client.publish_json("orders", &json!({}), settings).await;
+    assert!(matches!(result, Err(QStashError::InvalidHeaderValue(_))));
+}
+
+#[tokio::test]
+async fn publish_sends_the_client_default_retries_unless_overridden() {
+    let server = MockServer::start().await;
+    for retries in ["2", "5"] {
+        Mock::given(method("POST"))
+            .and(header("Upstash-Retries", retries))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .default_retries(2)
+        .build()
+        .unwrap();
+    client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .unwrap();
+    let settings = MessageSettings::new().retries(5);
+    client
+        .publish_json("orders", &json!({}), settings)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn publish_fanout_returns_a_result_per_url() {
+    let (server, client) = common::setup().await;
+    for (url, response) in [
+        (
+            "a",
+            ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_a" })),
+        ),
+        (
+            "b",
+            ResponseTemplate::new(500).set_body_string("internal error"),
+        ),
+        (
+            "c",
+            ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_c" })),
+        ),
+    ] {
+        Mock::given(method("POST"))
+            .and(path(format!(
+                "/v2/publish/https://{}.example.com/hook",
+                url
+            )))
+            .respond_with(response)
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    let urls = [
+        "https://a.example.com/hook",
+        "https://b.example.com/hook",
+        "https://c.example.com/hook",
+    ];
+    let results = client
+        .publish_fanout(&urls, &json!({}), None, Some(2))
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap().message_id, "msg_a");
+    assert!(matches!(
+        results[1],
+        Err(QStashError::Api { status: 500, .. })
+    ));
+    assert_eq!(results[2].as_ref().unwrap().message_id, "msg_c");
+}
+
+#[tokio::test]
+async fn per_message_timeouts_only_apply_to_their_request() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "messageId": "msg_1" }))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&server)
+        .await;
+
+    let settings = MessageSettings::new().timeout(Duration::from_millis(20));
+    match client.publish_json("orders", &json!({}), settings).await {
+        Err(QStashError::ClientError(e)) => assert!(e.is_timeout()),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+
+    client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn publish_takes_the_message_id_from_the_header_if_the_body_omits_it() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({}))
+                .insert_header("Upstash-Message-Id", "msg_from_header"),
+        )
+        .mount(&server)
+        .await;
+
+    let response = client
+        .publish_json("https://example.com/hook", &json!({}), None)
+        .await
+        .unwrap();
+    assert_eq!(response.message_ids(), vec!["msg_from_header"]);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn large_bodies_are_gzipped_when_compression_is_enabled() {
+    use std::io::Read;
+
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(header("Content-Encoding", "gzip"))
+        .and(header("Upstash-Forward-Content-Encoding", "gzip"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let body = json!({ "text": "compressible ".repeat(100) });
+    let settings = MessageSettings::new().compress(256);
+    client
+        .publish_json("orders", &body, settings)
+        .await
+        .unwrap();
+
+    let request = &server.received_requests().await.unwrap()[0];
+    assert!(request.body.len() < serde_json::to_vec(&body).unwrap().len());
+    let mut decompressed = String::new();
+    flate2::read::GzDecoder::new(&request.body[..])
+        .read_to_string(&mut decompressed)
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&decompressed).unwrap(),
+        body
+    );
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn small_bodies_are_not_compressed() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(body_json(json!({ "id": 1 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let settings = MessageSettings::new().compress(256);
+    client
+        .publish_json("orders", &json!({ "id": 1 }), settings)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn publish_requests_are_sent_with_their_settings() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/orders"))
+        .and(header("Content-Type", "text/plain"))
+        .and(header("Upstash-Delay", "10s"))
+        .and(body_bytes(b"hello".to_vec()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{ "messageId": "msg_1" }])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = PublishRequest::new()
+        .destination(Destination::Topic("orders".to_string()))
+        .bytes_body(b"hello".to_vec(), "text/plain")
+        .settings(MessageSettings::new().delay("10s"))
+        .send(&client)
+        .await
+        .unwrap();
+    assert_eq!(response.message_ids(), vec!["msg_1"]);
+}
+
+#[tokio::test]
+async fn publish_requests_require_a_destination() {
+    let (_server, client) = common::setup().await;
+    let result = PublishRequest::new()
+        .json_body(&json!({}))
+        .send(&client)
+        .await;
+    assert!(matches!(result, Err(QStashError::MissingDestination)));
+}
+
+#[tokio::test]
+async fn default_headers_are_merged_with_message_headers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(header("Upstash-Forward-Tenant", "acme"))
+        .and(header("Upstash-Forward-Env", "staging"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut default_headers = reqwest::header::HeaderMap::new();
+    default_headers.insert("Upstash-Forward-Tenant", "acme".parse().unwrap());
+    default_headers.insert("Upstash-Forward-Env", "production".parse().unwrap());
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .default_headers(default_headers)
+        .build()
+        .unwrap();
+
+    let settings = MessageSettings::new()
+        .headers_from([("Upstash-Forward-Env", "staging")])
+        .unwrap();
+    client
+        .publish_json("orders", &json!({}), settings)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn bodies_over_the_size_limit_are_rejected_before_sending() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .max_body_size(10)
+        .build()
+        .unwrap();
+
+    let result = client
+        .publish_bytes("orders", vec![b'x'; 11], "text/plain", None)
+        .await;
+    assert!(matches!(
+        result,
+        Err(QStashError::BodyTooLarge {
+            size: 11,
+            limit: 10
+        })
+    ));
+
+    client
+        .publish_bytes("orders", vec![b'x'; 10], "text/plain", None)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn retries_above_the_configured_limit_are_rejected() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(&server))
+        .retries_limit(3)
+        .build()
+        .unwrap();
+
+    let result = client
+        .publish_json("orders", &json!({}), MessageSettings::new().retries(5))
+        .await;
+    assert!(matches!(
+        result,
+        Err(QStashError::InvalidRetries {
+            retries: 5,
+            limit: 3
+        })
+    ));
+
+    client
+        .publish_json("orders", &json!({}), MessageSettings::new().retries(3))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn publish_form_encodes_the_pairs() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/publish/https://example.com/form"))
+        .and(header("Content-Type", "application/x-www-form-urlencoded"))
+        .and(body_bytes(b"name=Jane+Doe&note=a%26b%3Dc".to_vec()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .publish_form(
+            "https://example.com/form",
+            &[("name", "Jane Doe"), ("note", "a&b=c")],
+            None,
+        )
+        .await
+        .unwrap();
+}
+
+#[derive(serde::Serialize)]
+struct Signup {
+    id: u32,
+    email: &'static str,
+}
+
+#[tokio::test]
+async fn estimated_payload_sizes_match_the_sent_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .mount(&server)
+        .await;
+
+    let signup = Signup {
+        id: 42,
+        email: "user@example.com",
+    };
+    // {"id":42,"email":"user@example.com"}
+    assert_eq!(client.estimate_payload_size(&signup).unwrap(), 36);
+
+    client.publish_json("orders", &signup, None).await.unwrap();
+    let request = &server.received_requests().await.unwrap()[0];
+    assert_eq!(request.body.len(), 36);
+}
+
+#[tokio::test]
+async fn topic_names_with_dot_segments_are_rejected() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    for topic in ["..", "../keys"] {
+        assert!(matches!(
+            client.publish_json(topic, &json!({}), None).await,
+            Err(QStashError::InvalidPathSegment(_))
+        ));
+        assert!(matches!(
+            client
+                .publish_to_queue("orders", topic, &json!({}), None)
+                .await,
+            Err(QStashError::InvalidPathSegment(_))
+        ));
+    }
+    assert!(matches!(
+        client
+            .publish(Destination::Topic("..".to_string()), &json!({}), None)
+            .await,
+        Err(QStashError::InvalidPathSegment(_))
+    ));
+}
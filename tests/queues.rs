@@ -0,0 +1,78 @@
+mod common;
+
+use serde_json::json;
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn create_queue_sends_the_name_and_parallelism() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/queues"))
+        .and(body_json(
+            json!({ "queueName": "orders", "parallelism": 2 }),
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.create_queue("orders", 2).await.unwrap();
+}
+
+#[tokio::test]
+async fn list_queues_deserializes_each_queue() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/queues"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "name": "orders", "parallelism": 1, "lag": 3, "createdAt": 1_700_000_000_000_i64 },
+            { "name": "emails" }
+        ])))
+        .mount(&server)
+        .await;
+
+    let queues = client.list_queues().await.unwrap();
+    assert_eq!(queues.len(), 2);
+    assert_eq!(queues[0].name, "orders");
+    assert_eq!(queues[0].parallelism, 1);
+    assert_eq!(queues[0].lag, Some(3));
+    assert_eq!(queues[1].name, "emails");
+    assert_eq!(queues[1].lag, None);
+}
+
+#[tokio::test]
+async fn delete_queue_sends_the_name_in_the_path() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/queues/orders"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.delete_queue("orders").await.unwrap();
+}
+
+#[tokio::test]
+async fn publish_to_queue_enqueues_the_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/enqueue/orders/https://example.com/hook"))
+        .and(body_json(json!({ "id": 1 })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client
+        .publish_to_queue(
+            "orders",
+            "https://example.com/hook",
+            &json!({ "id": 1 }),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.message_ids(), vec!["msg_1"]);
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use upstash_qstash::{Client, ClientBuilder, QStashError};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn builder(server: &MockServer) -> ClientBuilder {
+    Client::builder()
+        .token(common::TOKEN)
+        .base_url(&common::base_url(server))
+        .max_retries(3)
+        .retry_backoff(Duration::from_millis(1))
+}
+
+async fn fail_then_succeed(server: &MockServer, failure: ResponseTemplate, failures: u64) {
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .respond_with(failure)
+        .up_to_n_times(failures)
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/quota"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "used": 1, "max": 500 })))
+        .mount(server)
+        .await;
+}
+
+async fn attempts(server: &MockServer) -> usize {
+    server.received_requests().await.unwrap().len()
+}
+
+#[tokio::test]
+async fn transient_failures_are_retried_until_the_request_succeeds() {
+    let server = MockServer::start().await;
+    fail_then_succeed(&server, ResponseTemplate::new(503), 2).await;
+
+    let quota = builder(&server).build().unwrap().get_quota().await.unwrap();
+    assert_eq!(quota.used, 1);
+    assert_eq!(attempts(&server).await, 3);
+}
+
+#[tokio::test]
+async fn requests_are_not_retried_by_default() {
+    let server = MockServer::start().await;
+    fail_then_succeed(&server, ResponseTemplate::new(503), 1).await;
+
+    let client = common::setup_with_server(&server);
+    assert!(client.get_quota().await.is_err());
+    assert_eq!(attempts(&server).await, 1);
+}
+
+#[tokio::test]
+async fn retries_stop_after_the_maximum() {
+    let server = MockServer::start().await;
+    fail_then_succeed(&server, ResponseTemplate::new(503), 10).await;
+
+    let result = builder(&server).build().unwrap().get_quota().await;
+    assert!(matches!(result, Err(QStashError::Api { status: 503, .. })));
+    assert_eq!(attempts(&server).await, 4);
+}
+
+#[tokio::test]
+async fn publishes_without_deduplication_are_not_retried() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&server)
+        .await;
+
+    let client = builder(&server).build().unwrap();
+    assert!(client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .is_err());
+    assert_eq!(attempts(&server).await, 1);
+}
+
+#[tokio::test]
+async fn rate_limits_resetting_after_the_maximum_delay_are_not_waited_for() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+        .mount(&server)
+        .await;
+
+    let start = Instant::now();
+    let result = builder(&server).build().unwrap().get_quota().await;
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(matches!(
+        result,
+        Err(QStashError::RateLimited { retry_after }) if retry_after == Duration::from_secs(3600)
+    ));
+    assert_eq!(attempts(&server).await, 1);
+}
+
+#[tokio::test]
+async fn rate_limits_resetting_within_the_maximum_delay_are_retried() {
+    let server = MockServer::start().await;
+    let rate_limited = ResponseTemplate::new(429).insert_header("Retry-After", "1");
+    fail_then_succeed(&server, rate_limited, 1).await;
+
+    let client = builder(&server)
+        .retry_max_delay(Duration::from_secs(2))
+        .build()
+        .unwrap();
+    client.get_quota().await.unwrap();
+    assert_eq!(attempts(&server).await, 2);
+}
+
+#[tokio::test]
+async fn only_the_configured_statuses_are_retried() {
+    let server = MockServer::start().await;
+    fail_then_succeed(&server, ResponseTemplate::new(500), 1).await;
+
+    let client = builder(&server).retry_on(&[503]).build().unwrap();
+    let result = client.get_quota().await;
+    assert!(matches!(result, Err(QStashError::Api { status: 500, .. })));
+    assert_eq!(attempts(&server).await, 1);
+
+    server.reset().await;
+    fail_then_succeed(&server, ResponseTemplate::new(503), 1).await;
+    client.get_quota().await.unwrap();
+    assert_eq!(attempts(&server).await, 2);
+}
+
+#[tokio::test]
+async fn server_errors_are_not_retried_by_default() {
+    let server = MockServer::start().await;
+    fail_then_succeed(&server, ResponseTemplate::new(500), 1).await;
+
+    let result = builder(&server).build().unwrap().get_quota().await;
+    assert!(matches!(result, Err(QStashError::Api { status: 500, .. })));
+    assert_eq!(attempts(&server).await, 1);
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use serde_json::json;
+use upstash_qstash::{QStashError, ScheduleBuilder};
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn create_schedule_sends_the_cron_header_to_the_destination_path() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/schedules/https://example.com/hook"))
+        .and(header("Upstash-Cron", "*/5 * * * *"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "scheduleId": "scd_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = client
+        .create_schedule("https://example.com/hook", "*/5 * * * *", None)
+        .await
+        .unwrap();
+    assert_eq!(response.schedule_id, "scd_1");
+}
+
+#[tokio::test]
+async fn list_schedules_deserializes_multiple_schedules() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/schedules"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "scheduleId": "scd_1",
+                "cron": "0 * * * *",
+                "destination": "https://example.com/hook",
+                "createdAt": 1_700_000_000_000_i64,
+                "settings": { "retries": 3 },
+                "isPaused": false
+            },
+            { "scheduleId": "scd_2", "cron": "0 0 * * *", "destination": "orders" }
+        ])))
+        .mount(&server)
+        .await;
+
+    let schedules = client.list_schedules().await.unwrap();
+    assert_eq!(schedules.len(), 2);
+    assert_eq!(schedules[0].schedule_id, "scd_1");
+    assert_eq!(schedules[0].cron.as_deref(), Some("0 * * * *"));
+    assert!(schedules[0].created_at.is_some());
+    assert_eq!(schedules[0].settings, Some(json!({ "retries": 3 })));
+    assert_eq!(schedules[1].destination.as_deref(), Some("orders"));
+    assert!(schedules[1].created_at.is_none());
+}
+
+#[tokio::test]
+async fn get_schedule_returns_the_schedule() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/schedules/scd_1"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "scheduleId": "scd_1", "cron": "0 * * * *" })),
+        )
+        .mount(&server)
+        .await;
+
+    let schedule = client.get_schedule("scd_1").await.unwrap();
+    assert_eq!(schedule.schedule_id, "scd_1");
+}
+
+#[tokio::test]
+async fn delete_schedule_accepts_an_empty_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/schedules/scd_1"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.delete_schedule("scd_1").await.unwrap();
+}
+
+#[tokio::test]
+async fn missing_schedules_return_an_error() {
+    let (server, client) = common::setup().await;
+    Mock::given(path("/v2/schedules/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_string("schedule not found"))
+        .mount(&server)
+        .await;
+
+    assert!(matches!(
+        client.get_schedule("missing").await,
+        Err(QStashError::NotFound(message)) if message == "schedule not found"
+    ));
+    assert!(matches!(
+        client.delete_schedule("missing").await,
+        Err(QStashError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn pause_and_resume_schedule_post_to_their_endpoints() {
+    let (server, client) = common::setup().await;
+    for action in ["pause", "resume"] {
+        Mock::given(method("POST"))
+            .and(path(format!("/v2/schedules/scd_1/{}", action)))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+    }
+
+    client.pause_schedule("scd_1").await.unwrap();
+    client.resume_schedule("scd_1").await.unwrap();
+}
+
+#[tokio::test]
+async fn schedule_builder_sends_all_headers() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/schedules/https://example.com/report"))
+        .and(header("Upstash-Cron", "0 9 * * 1"))
+        .and(header("Upstash-Retries", "2"))
+        .and(header("Upstash-Callback", "https://example.com/callback"))
+        .and(header("Upstash-Forward-X-Report", "weekly"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "scheduleId": "scd_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let response = ScheduleBuilder::new()
+        .destination("https://example.com/report")
+        .cron("0 9 * * 1")
+        .retries(2)
+        .callback("https://example.com/callback")
+        .forward_header("X-Report", "weekly")
+        .create(&client)
+        .await
+        .unwrap();
+    assert_eq!(response.schedule_id, "scd_1");
+}
+
+#[tokio::test]
+async fn schedule_builder_requires_a_destination_and_cron() {
+    let (_server, client) = common::setup().await;
+    let missing_destination = ScheduleBuilder::new()
+        .cron("0 9 * * 1")
+        .create(&client)
+        .await;
+    assert!(matches!(
+        missing_destination,
+        Err(QStashError::MissingDestination)
+    ));
+
+    let missing_cron = ScheduleBuilder::new()
+        .destination("https://example.com/report")
+        .create(&client)
+        .await;
+    assert!(matches!(missing_cron, Err(QStashError::MissingCron)));
+}
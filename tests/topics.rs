@@ -0,0 +1,106 @@
+mod common;
+
+use serde_json::json;
+use upstash_qstash::QStashError;
+use wiremock::matchers::{body_json, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test]
+async fn add_endpoint_sends_the_endpoint() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/topics/orders/endpoints"))
+        .and(body_json(json!({
+            "endpoints": [{ "name": "billing", "url": "https://example.com/billing" }]
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client
+        .add_endpoint("orders", "billing", "https://example.com/billing")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn remove_endpoint_sends_the_endpoint_name() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/topics/orders/endpoints"))
+        .and(body_json(json!({ "endpoints": [{ "name": "billing" }] })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.remove_endpoint("orders", "billing").await.unwrap();
+}
+
+#[tokio::test]
+async fn list_topics_deserializes_topics_with_multiple_endpoints() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .and(path("/v2/topics"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "name": "orders",
+            "createdAt": 1_700_000_000_000_i64,
+            "endpoints": [
+                { "name": "billing", "url": "https://example.com/billing", "topicName": "orders" },
+                { "url": "https://example.com/shipping" }
+            ]
+        }])))
+        .mount(&server)
+        .await;
+
+    let topics = client.list_topics().await.unwrap();
+    assert_eq!(topics.len(), 1);
+    assert_eq!(topics[0].name, "orders");
+    assert_eq!(topics[0].endpoints.len(), 2);
+    assert_eq!(topics[0].endpoints[0].name.as_deref(), Some("billing"));
+    assert_eq!(topics[0].endpoints[1].name, None);
+    assert_eq!(topics[0].endpoints[1].url, "https://example.com/shipping");
+}
+
+#[tokio::test]
+async fn delete_topic_accepts_an_empty_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/topics/orders"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.delete_topic("orders").await.unwrap();
+}
+
+#[tokio::test]
+async fn names_are_sent_as_a_single_path_segment() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("DELETE"))
+        .and(path("/v2/topics/a%3Fb"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v2/messages/..%2Fquota"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client.delete_topic("a?b").await.unwrap();
+    client.get_message("../quota").await.unwrap();
+}
+
+#[tokio::test]
+async fn dot_segments_are_rejected() {
+    let (_server, client) = common::setup().await;
+    assert!(matches!(
+        client.delete_topic("..").await,
+        Err(QStashError::InvalidPathSegment(name)) if name == ".."
+    ));
+}
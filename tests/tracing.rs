@@ -0,0 +1,89 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use serde_json::json;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+use wiremock::matchers::method;
+use wiremock::{Mock, ResponseTemplate};
+
+/// Collects the formatted logs, to assert on them.
+#[derive(Clone, Default)]
+struct Logs(Arc<Mutex<Vec<u8>>>);
+
+impl Logs {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for Logs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Logs {
+    type Writer = Logs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn subscriber(logs: &Logs) -> impl tracing::Subscriber {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(logs.clone())
+        .finish()
+}
+
+#[tokio::test]
+async fn publish_is_traced_without_the_token() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "messageId": "msg_1" })))
+        .mount(&server)
+        .await;
+
+    let logs = Logs::default();
+    let _guard = tracing::subscriber::set_default(subscriber(&logs));
+    client
+        .publish_json("orders", &json!({}), None)
+        .await
+        .unwrap();
+
+    let logs = logs.contents();
+    assert!(logs.contains("qstash_request"), "{}", logs);
+    assert!(logs.contains("method=POST"), "{}", logs);
+    assert!(logs.contains("path=\"/v2/publish/orders\""), "{}", logs);
+    assert!(logs.contains("status=200"), "{}", logs);
+    assert!(!logs.contains(common::TOKEN), "{}", logs);
+}
+
+#[tokio::test]
+async fn error_responses_are_logged_with_their_status_and_body() {
+    let (server, client) = common::setup().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("internal error"))
+        .mount(&server)
+        .await;
+
+    let logs = Logs::default();
+    let _guard = tracing::subscriber::set_default(subscriber(&logs));
+    assert!(client.get_quota().await.is_err());
+
+    let logs = logs.contents();
+    assert!(logs.contains("WARN"), "{}", logs);
+    assert!(logs.contains("status=500"), "{}", logs);
+    assert!(logs.contains("internal error"), "{}", logs);
+    assert!(!logs.contains(common::TOKEN), "{}", logs);
+}